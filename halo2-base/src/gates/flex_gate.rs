@@ -26,6 +26,40 @@ pub enum GateStrategy {
     PlonkPlus,
 }
 
+/// Numbers of calls to common `GateInstructions` methods, for estimating advice usage with
+/// [`FlexGateConfig::estimate_rows`] before assigning anything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GateOpCounts {
+    /// single basic gate methods: `add`, `sub`, `neg`, `mul`, `mul_add`, `mul_not`, `div_unsafe`, `assert_bit`
+    pub basic: usize,
+    pub select: usize,
+    pub is_zero: usize,
+    pub is_equal: usize,
+    /// number of `inner_product` calls
+    pub inner_products: usize,
+    /// total length of all `inner_product` calls
+    pub inner_product_terms: usize,
+}
+
+impl GateOpCounts {
+    /// Returns the number of advice cells these operations use with `GateStrategy::Vertical` when no argument is a
+    /// `Constant`. This is exact in that case, and an upper bound when arguments are constants or with
+    /// `GateStrategy::PlonkPlus`, whose layouts never use more cells.
+    pub fn advice_cells(&self) -> usize {
+        // | a | b | c | a + b * c |
+        4 * self.basic
+            // | a - b | 1 | b | a | b | sel | a - b | out |
+            + 8 * self.select
+            // | out | a | a^{-1} | 1 | 0 | a | out | 0 |
+            + 8 * self.is_zero
+            // `sub` followed by `is_zero`
+            + 12 * self.is_equal
+            // | 0 | a_0 | b_0 | acc_0 | a_1 | b_1 | acc_1 | ...
+            + self.inner_products
+            + 3 * self.inner_product_terms
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BasicGateConfig<F: ScalarField> {
     // `q_enable` will have either length 1 or 2, depending on the strategy
@@ -182,26 +216,40 @@ impl<F: ScalarField> FlexGateConfig<F> {
         }
     }
 
+    /// Estimates the `max_rows` needed for the operations in `ops`, i.e., the number of rows used per phase 0 advice
+    /// column, as `ceil(ops.advice_cells() / num_advice)`.
+    ///
+    /// This is a lower bound on the rows actually used even when `ops.advice_cells()` is exact: a region is never split
+    /// across columns, so each column may end with up to one region's worth of unused rows. It does not count lookup
+    /// advice columns, constants in fixed columns, or the blinding rows excluded from `max_rows`.
+    pub fn estimate_rows(&self, ops: &GateOpCounts) -> usize {
+        let num_advice = self.num_advice[0];
+        (ops.advice_cells() + num_advice - 1) / num_advice
+    }
+
     pub fn inner_product_simple<'a, 'b: 'a>(
         &self,
         ctx: &mut Context<'_, F>,
         a: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
         b: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
     ) -> AssignedValue<'b, F> {
-        let mut sum;
-        let mut a = a.into_iter();
-        let mut b = b.into_iter().peekable();
+        // a leading run of products of two constants is folded into the starting value of the accumulator, so it uses no advice cells
+        let mut pairs = a.into_iter().zip(b).peekable();
+        let mut const_sum = F::zero();
+        while let Some((Constant(a), Constant(b))) = pairs.peek() {
+            const_sum += *a * b;
+            pairs.next();
+        }
 
-        let cells = if matches!(b.peek(), Some(Constant(c)) if c == &F::one()) {
-            b.next();
-            let start_a = a.next().unwrap();
-            sum = start_a.value().copied();
-            iter::once(start_a)
+        let start = if const_sum == F::zero()
+            && matches!(pairs.peek(), Some((_, Constant(c))) if c == &F::one())
+        {
+            pairs.next().unwrap().0
         } else {
-            sum = Value::known(F::zero());
-            iter::once(Constant(F::zero()))
-        }
-        .chain(a.zip(b).flat_map(|(a, b)| {
+            Constant(const_sum)
+        };
+        let mut sum = start.value().copied();
+        let cells = iter::once(start).chain(pairs.flat_map(|(a, b)| {
             sum = sum + a.value().zip(b.value()).map(|(a, b)| *a * b);
             [a, b, Witness(sum)]
         }));
@@ -362,11 +410,12 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
         // we index into `advice_alloc` twice so this assert should save a bound check
         assert!(self.context_id < ctx.advice_alloc.len(), "context id out of bounds");
 
+        let base_row = ctx.row_offset();
         let (gate_index, row_offset) = {
             let alloc = ctx.advice_alloc.get_mut(self.context_id).unwrap();
 
             if alloc.1 + len >= ctx.max_rows {
-                alloc.1 = 0;
+                alloc.1 = base_row;
                 alloc.0 += 1;
             }
             *alloc
@@ -396,6 +445,8 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
                 .q_enable
                 .enable(&mut ctx.region, (row_offset as isize + i) as usize)
                 .expect("enable selector should not fail");
+            #[cfg(feature = "display")]
+            ctx.mark_rows_constrained(column, (row_offset as isize + i) as usize, 4);
 
             if self.strategy == GateStrategy::PlonkPlus {
                 let q_coeff = q_coeff.unwrap_or([F::one(), F::zero(), F::zero()]);
@@ -450,11 +501,12 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
         // we index into `advice_alloc` twice so this assert should save a bound check
         assert!(self.context_id < ctx.advice_alloc.len(), "context id out of bounds");
 
+        let base_row = ctx.row_offset();
         let (gate_index, row_offset) = {
             let alloc = ctx.advice_alloc.get_mut(self.context_id).unwrap();
 
             if alloc.1 + len >= ctx.max_rows {
-                alloc.1 = 0;
+                alloc.1 = base_row;
                 alloc.0 += 1;
             }
             *alloc
@@ -482,6 +534,8 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
                 .q_enable
                 .enable(&mut ctx.region, (row_offset as isize + i) as usize)
                 .expect("selector enable should not fail");
+            #[cfg(feature = "display")]
+            ctx.mark_rows_constrained(column, (row_offset as isize + i) as usize, 4);
 
             if self.strategy == GateStrategy::PlonkPlus {
                 let q_coeff = q_coeff.unwrap_or([F::one(), F::zero(), F::zero()]);
@@ -592,10 +646,11 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
                 let phase = ctx.current_phase();
                 assert!(self.context_id < ctx.advice_alloc.len(), "context id out of bounds");
 
+                let base_row = ctx.row_offset();
                 let (gate_index, mut row_offset) = {
                     let alloc = ctx.advice_alloc.get_mut(self.context_id).unwrap();
                     if alloc.1 + 3 * len + 1 >= ctx.max_rows {
-                        alloc.1 = 0;
+                        alloc.1 = base_row;
                         alloc.0 += 1;
                     }
                     *alloc
@@ -636,6 +691,8 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
                     q_enable
                         .enable(&mut ctx.region, row_offset - 1)
                         .expect("enable selector should not fail");
+                    #[cfg(feature = "display")]
+                    ctx.mark_rows_constrained(column, row_offset - 1, 4);
 
                     acc = acc + a.value().zip(b.value()).map(|(a, b)| *a * b);
                     let [a, _, c] = [(a, 0), (b, 1), (Witness(acc), 2)].map(|(qcell, idx)| {
@@ -714,10 +771,34 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
     ) -> AssignedValue<'v, F> {
         let diff_val: Value<F> = a.value().zip(b.value()).map(|(a, b)| *a - b);
         let out_val = diff_val * sel.value() + b.value();
-        match self.strategy {
+        match (self.strategy, &a, &b) {
+            // constant branches are folded into constant cells instead of being copied around
+            // | b | sel | a - b | out |
+            (GateStrategy::Vertical, Constant(a), Constant(b)) => self.assign_region_last(
+                ctx,
+                vec![Constant(*b), sel, Constant(*a - b), Witness(out_val)],
+                vec![(0, None)],
+            ),
+            // | b | -b | sel | b - b * sel | sel | a | out |
+            (GateStrategy::Vertical, _, Constant(b)) => {
+                let b = *b;
+                let b_not_sel_val = sel.value().map(|sel| b - b * sel);
+                let cells = vec![
+                    Constant(b),
+                    Constant(-b),
+                    sel.clone(),
+                    Witness(b_not_sel_val),
+                    sel,
+                    a,
+                    Witness(out_val),
+                ];
+                let mut assigned_cells =
+                    self.assign_region_smart(ctx, cells, vec![0, 3], vec![(2, 4)], vec![]);
+                assigned_cells.pop().unwrap()
+            }
             // | a - b | 1 | b | a |
             // | b | sel | a - b | out |
-            GateStrategy::Vertical => {
+            (GateStrategy::Vertical, _, _) => {
                 let cells = vec![
                     Witness(diff_val),
                     Constant(F::one()),
@@ -736,7 +817,7 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
             // selectors
             // | 1 | 0 | 0     | 1 | 0   | 0
             // | 0 | 1 | -1    | 1 | 0   | 0
-            GateStrategy::PlonkPlus => {
+            (GateStrategy::PlonkPlus, _, _) => {
                 let mut assignments = self.assign_region(
                     ctx,
                     vec![
@@ -750,7 +831,7 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
                     ],
                     vec![(0, Some([F::zero(), F::one(), -F::one()])), (3, None)],
                 );
-                ctx.region.constrain_equal(assignments[2].cell(), assignments[5].cell());
+                ctx.constrain_equal(&assignments[2], &assignments[5]);
                 assignments.pop().unwrap()
             }
         }
@@ -815,7 +896,7 @@ impl<F: ScalarField> GateInstructions<F> for FlexGateConfig<F> {
             self.pow_of_two[..range_bits].iter().map(|c| Constant(*c)),
             &mut bit_cells,
         );
-        ctx.region.constrain_equal(a.cell(), acc.cell());
+        ctx.constrain_equal(a, &acc);
 
         for bit_cell in &bit_cells {
             self.assign_region(
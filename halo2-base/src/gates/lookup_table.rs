@@ -0,0 +1,150 @@
+//! Lookup tables for arbitrary functions `f: F -> F` given by their `(x, f(x))` entries, e.g., an S-box.
+//!
+//! `Context` only holds a `Region`, so tables are loaded through the `Layouter` like the range lookup table of
+//! [`RangeConfig`](super::range::RangeConfig): configure a [`LookupTableConfig`], call
+//! [`LookupTableConfig::load_lookup_table`] in `synthesize` to get a [`LookupTable`] handle, and then use
+//! [`LookupTableConfig::lookup`] inside the region.
+use crate::{
+    halo2_proofs::{
+        circuit::{Layouter, Value},
+        plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+        poly::Rotation,
+    },
+    utils::ScalarField,
+    AssignedValue, Context,
+    QuantumCell::{Existing, Witness},
+};
+use rustc_hash::FxHashMap;
+use std::marker::PhantomData;
+
+/// The entries of a table loaded with [`LookupTableConfig::load_lookup_table`], used to witness lookup outputs
+#[derive(Clone, Debug)]
+pub struct LookupTable<F: ScalarField> {
+    // keyed by `to_repr` bytes since PSE's halo2curves does not derive Hash
+    entries: FxHashMap<Vec<u8>, F>,
+}
+
+impl<F: ScalarField> LookupTable<F> {
+    /// Returns `f(x)` if `x` is an input of the table
+    pub fn get(&self, x: &F) -> Option<F> {
+        self.entries.get(x.to_repr().as_ref()).copied()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LookupTableConfig<F: ScalarField> {
+    // each lookup uses one row of `input_advice` and `output_advice`, allocated with `ctx.advice_alloc[context_id]`
+    pub q_lookup: Selector,
+    pub input_advice: Column<Advice>,
+    pub output_advice: Column<Advice>,
+    // `tag` is 1 on every entry and 0 on the padding row `(0, 0, 0)`, so rows with `q_lookup` off always match the
+    // padding row while rows with `q_lookup` on can only match real entries
+    pub tag: TableColumn,
+    pub input: TableColumn,
+    pub output: TableColumn,
+    pub context_id: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: ScalarField> LookupTableConfig<F> {
+    /// Only supports lookups in phase 0. `context_id` must be distinct from the context ids of the other configs
+    /// sharing the `Context`, since it is used to allocate rows in `input_advice` and `output_advice`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, context_id: usize) -> Self {
+        let q_lookup = meta.complex_selector();
+        let input_advice = meta.advice_column();
+        let output_advice = meta.advice_column();
+        meta.enable_equality(input_advice);
+        meta.enable_equality(output_advice);
+        let [tag, input, output] = [(); 3].map(|_| meta.lookup_table_column());
+
+        meta.lookup("function lookup", |meta| {
+            let q = meta.query_selector(q_lookup);
+            let x = meta.query_advice(input_advice, Rotation::cur());
+            let y = meta.query_advice(output_advice, Rotation::cur());
+            vec![(q.clone(), tag), (q.clone() * x, input), (q * y, output)]
+        });
+
+        Self {
+            q_lookup,
+            input_advice,
+            output_advice,
+            tag,
+            input,
+            output,
+            context_id,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Assigns `entries` to the table columns and returns the handle to pass to `lookup`.
+    /// Each config holds a single table, so this should be called once per `synthesize`.
+    pub fn load_lookup_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        entries: &[(F, F)],
+    ) -> Result<LookupTable<F>, Error> {
+        layouter.assign_table(
+            || "function lookup",
+            |mut table| {
+                let rows = std::iter::once((F::zero(), F::zero(), F::zero()))
+                    .chain(entries.iter().map(|(x, y)| (F::one(), *x, *y)));
+                for (idx, (tag, x, y)) in rows.enumerate() {
+                    for (column, value) in [(self.tag, tag), (self.input, x), (self.output, y)] {
+                        table.assign_cell(
+                            || "function lookup table",
+                            column,
+                            idx,
+                            || Value::known(value),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        let entries = entries.iter().map(|(x, y)| (x.to_repr().as_ref().to_vec(), *y)).collect();
+        Ok(LookupTable { entries })
+    }
+
+    /// Returns `f(input)` for the function `f` of `table`, constrained by a lookup.
+    ///
+    /// If `input` is not in the table, the output is witnessed as 0 and the circuit is unsatisfiable.
+    pub fn lookup<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        table: &LookupTable<F>,
+        input: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(ctx.current_phase(), 0, "LookupTableConfig only supports lookups in phase 0");
+        let output_val = input.value().map(|x| table.get(x).unwrap_or_else(F::zero));
+        let row = ctx.advice_alloc[self.context_id].1;
+        assert!(row < ctx.max_rows, "NOT ENOUGH ROWS FOR FUNCTION LOOKUPS");
+
+        ctx.assign_cell(
+            Existing(input),
+            self.input_advice,
+            #[cfg(feature = "display")]
+            self.context_id,
+            row,
+            #[cfg(feature = "halo2-pse")]
+            0,
+        );
+        let output = ctx.assign_cell(
+            Witness(output_val),
+            self.output_advice,
+            #[cfg(feature = "display")]
+            self.context_id,
+            row,
+            #[cfg(feature = "halo2-pse")]
+            0,
+        );
+        self.q_lookup.enable(&mut ctx.region, row).expect("enable selector should not fail");
+        ctx.count_lookup();
+        #[cfg(feature = "display")]
+        {
+            ctx.mark_rows_constrained(self.input_advice, row, 1);
+            ctx.mark_rows_constrained(self.output_advice, row, 1);
+        }
+        ctx.advice_alloc[self.context_id].1 += 1;
+        output
+    }
+}
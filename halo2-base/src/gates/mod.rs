@@ -1,4 +1,8 @@
-use self::{flex_gate::GateStrategy, range::RangeStrategy};
+use self::{
+    flex_gate::GateStrategy,
+    range::RangeStrategy,
+    replay::{RecordedCell, RecordedGadget},
+};
 use super::{
     utils::ScalarField,
     AssignedValue, Context,
@@ -6,7 +10,7 @@ use super::{
 };
 use crate::{
     halo2_proofs::{circuit::Value, plonk::Assigned},
-    utils::{biguint_to_fe, bit_length, fe_to_biguint, PrimeField},
+    utils::{biguint_bits, biguint_to_fe, bit_length, decompose, fe_to_biguint, PrimeField},
 };
 use core::iter;
 use num_bigint::BigUint;
@@ -15,7 +19,9 @@ use num_traits::{One, Zero};
 use std::ops::Shl;
 
 pub mod flex_gate;
+pub mod lookup_table;
 pub mod range;
+pub mod replay;
 
 pub trait GateInstructions<F: ScalarField> {
     fn strategy(&self) -> GateStrategy;
@@ -78,10 +84,10 @@ pub trait GateInstructions<F: ScalarField> {
         let assignments =
             self.assign_region(ctx, inputs, gate_offsets.into_iter().map(|i| (i as isize, None)));
         for (offset1, offset2) in equality_offsets.into_iter() {
-            ctx.region.constrain_equal(assignments[offset1].cell(), assignments[offset2].cell());
+            ctx.constrain_equal(&assignments[offset1], &assignments[offset2]);
         }
         for (assigned, eq_offset) in external_equality.into_iter() {
-            ctx.region.constrain_equal(assigned.cell(), assignments[eq_offset].cell());
+            ctx.constrain_equal(assigned, &assignments[eq_offset]);
         }
         assignments
     }
@@ -102,10 +108,38 @@ pub trait GateInstructions<F: ScalarField> {
         self.assign_region_last(ctx, [Witness(witness)], [])
     }
 
+    /// Assigns `value` to a new advice cell, marks it public with `ctx.mark_public`, and returns the cell together with
+    /// its index among `ctx.public_cells()`, i.e., the instance row it will be wired to.
+    fn assign_public<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        value: Value<F>,
+    ) -> (AssignedValue<'v, F>, usize) {
+        let cell = self.load_witness(ctx, value);
+        let index = ctx.public_cells().len();
+        ctx.mark_public(&cell);
+        (cell, index)
+    }
+
     fn load_constant<'a>(&self, ctx: &mut Context<'_, F>, c: F) -> AssignedValue<'a, F> {
         self.assign_region_last(ctx, [Constant(c)], [])
     }
 
+    /// Copies `a` into a new advice cell in phase `phase` and constrains the two cells to be equal.
+    ///
+    /// Use this to reference a value computed in an earlier phase from a later one instead of re-witnessing it.
+    /// Advice is assigned one phase at a time, so `phase` must be `ctx.current_phase()` and `a` must have been
+    /// assigned in a phase `<= phase`.
+    fn copy_to_phase<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: &AssignedValue<'v, F>,
+        phase: usize,
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(phase, ctx.current_phase(), "can only copy into the current phase");
+        self.assign_region_last_in(ctx, [Existing(a)], [], phase)
+    }
+
     fn load_zero<'a>(&self, ctx: &mut Context<'a, F>) -> AssignedValue<'a, F> {
         if let Some(zcell) = &ctx.zero_cell {
             return zcell.clone();
@@ -198,7 +232,7 @@ pub trait GateInstructions<F: ScalarField> {
         let out_val = a.value().zip(b.value()).map(|(a, b)| (F::one() - a) * b);
         let assignments =
             self.assign_region(ctx, vec![Witness(out_val), a, b.clone(), b], vec![(0, None)]);
-        ctx.region.constrain_equal(assignments[2].cell(), assignments[3].cell());
+        ctx.constrain_equal(&assignments[2], &assignments[3]);
         assignments.into_iter().next().unwrap()
     }
 
@@ -211,6 +245,30 @@ pub trait GateInstructions<F: ScalarField> {
         );
     }
 
+    /// Returns 1 if `x` is 0 or 1 and 0 otherwise, as `is_zero((1 - x) * x)`.
+    ///
+    /// Unlike `assert_bit` this does not constrain `x`, e.g., for validating optional flags from external witnesses.
+    fn is_boolean<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        x: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let x_not_x = self.mul_not(ctx, Existing(x), Existing(x));
+        self.is_zero(ctx, &x_not_x)
+    }
+
+    /// Constrains `x != 0` by witnessing `x^{-1}` and constraining `x * x^{-1} = 1`
+    // | 0 | x | x^{-1} | 1 |
+    fn assert_not_zero(&self, ctx: &mut Context<'_, F>, x: &AssignedValue<F>) {
+        // if x = 0 there is no valid inverse, so we witness 0 and the constraint fails
+        let inv = x.value().map(|x| x.invert().unwrap_or(F::zero()));
+        self.assign_region_last(
+            ctx,
+            [Constant(F::zero()), Existing(x), Witness(inv), Constant(F::one())],
+            [(0, None)],
+        );
+    }
+
     fn div_unsafe<'v>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -227,7 +285,7 @@ pub trait GateInstructions<F: ScalarField> {
 
     fn assert_equal(&self, ctx: &mut Context<'_, F>, a: QuantumCell<F>, b: QuantumCell<F>) {
         if let (Existing(a), Existing(b)) = (&a, &b) {
-            ctx.region.constrain_equal(a.cell(), b.cell());
+            ctx.constrain_equal(a, b);
         } else {
             self.assign_region_smart(
                 ctx,
@@ -245,11 +303,65 @@ pub trait GateInstructions<F: ScalarField> {
         ctx.region.constrain_equal(a.cell(), &c_cell);
         #[cfg(feature = "halo2-pse")]
         ctx.region.constrain_equal(a.cell(), c_cell).unwrap();
+        #[cfg(feature = "display")]
+        ctx.mark_constrained(a);
+    }
+
+    /// Constrains `cells[i] = consts[i]` for every `i` with `assert_is_const`, i.e., with copy constraints to fixed cells
+    /// and no advice cells; repeated constants (e.g., padding) share one cached fixed cell.
+    ///
+    /// Panics if `cells` and `consts` have different lengths.
+    fn constrain_equal_to_consts(
+        &self,
+        ctx: &mut Context<'_, F>,
+        cells: &[AssignedValue<F>],
+        consts: &[F],
+    ) {
+        assert_eq!(
+            cells.len(),
+            consts.len(),
+            "constrain_equal_to_consts: cells and consts must have the same length"
+        );
+        for (cell, constant) in cells.iter().zip(consts) {
+            self.assert_is_const(ctx, cell, *constant);
+        }
+    }
+
+    /// Constrains `a = b` when `flag` is 1 and nothing when `flag` is 0, i.e., `flag * (a - b) = 0`.
+    /// Constrains `flag` to be a bit.
+    // | a - b | b | 1 | a |, | 0 | flag | a - b | 0 |
+    fn constrain_equal_if<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: &AssignedValue<'v, F>,
+        b: &AssignedValue<'v, F>,
+        flag: &AssignedValue<'v, F>,
+    ) {
+        self.assert_bit(ctx, flag);
+        let diff = self.sub(ctx, Existing(a), Existing(b));
+        self.assign_region_last(
+            ctx,
+            [Constant(F::zero()), Existing(flag), Existing(&diff), Constant(F::zero())],
+            [(0, None)],
+        );
+    }
+
+    /// Constrains `x` to be one of the elements of `set` by constraining `Prod_i (x - set[i]) = 0`
+    fn assert_in_set(&self, ctx: &mut Context<'_, F>, x: &AssignedValue<F>, set: &[F]) {
+        assert!(!set.is_empty());
+        let mut prod = self.sub(ctx, Existing(x), Constant(set[0]));
+        for v in set.iter().skip(1) {
+            let diff = self.sub(ctx, Existing(x), Constant(*v));
+            prod = self.mul(ctx, Existing(&prod), Existing(&diff));
+        }
+        self.assert_is_const(ctx, &prod, F::zero());
     }
 
-    /// Returns `(assignments, output)` where `output` is the inner product of `<a, b>`
+    /// Returns the inner product of `<a, b>`
     ///
-    /// `assignments` is for internal use
+    /// Entries of `a` and `b` may freely mix `Existing`, `Witness`, and `Constant` cells (pass a slice with `.iter().cloned()`).
+    /// With `GateStrategy::Vertical`, leading terms where both factors are `Constant` are folded into a single constant and use no extra advice cells,
+    /// so put constant pairs first.
     fn inner_product<'a, 'b: 'a>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -257,6 +369,148 @@ pub trait GateInstructions<F: ScalarField> {
         b: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
     ) -> AssignedValue<'b, F>;
 
+    /// Returns the inner product of the first `len` entries of `a` and `b`, e.g., when the remaining entries are known
+    /// to be zero, so that no gates are spent on the zero tail.
+    ///
+    /// Panics if `len` is larger than the length of `a` or `b`.
+    fn inner_product_prefix<'a, 'b: 'a>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
+        b: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
+        len: usize,
+    ) -> AssignedValue<'b, F> {
+        let a = a.into_iter().collect::<Vec<_>>();
+        let b = b.into_iter().collect::<Vec<_>>();
+        assert!(
+            len <= a.len() && len <= b.len(),
+            "inner_product_prefix: len {len} exceeds vector lengths {} and {}",
+            a.len(),
+            b.len()
+        );
+        self.inner_product(ctx, a.into_iter().take(len), b.into_iter().take(len))
+    }
+
+    /// Returns `sum_i coeffs[i] * xs[i]` for constant coefficients `coeffs`
+    ///
+    /// With `GateStrategy::PlonkPlus` the coefficients are folded into the fixed selector column.
+    fn lincomb<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        coeffs: &[F],
+        xs: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(coeffs.len(), xs.len(), "lincomb: coeffs and xs must have the same length");
+        self.inner_product(ctx, xs.iter().map(Existing), coeffs.iter().map(|c| Constant(*c)))
+    }
+
+    /// Returns `sum_i weights[i] * values[i]`, where either side of each term may be a constant or a witness.
+    ///
+    /// Products of two constants are summed on the host. Terms with one constant side are collected into a single
+    /// `inner_product` against constants, which with `GateStrategy::PlonkPlus` folds the constants into the fixed selector
+    /// column. The remaining witness-by-witness products are then accumulated starting from that partial sum.
+    fn weighted_sum<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        weights: &[QuantumCell<'_, 'v, F>],
+        values: &[QuantumCell<'_, 'v, F>],
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(
+            weights.len(),
+            values.len(),
+            "weighted_sum: weights and values must have the same length"
+        );
+        let mut const_sum = F::zero();
+        let (mut xs, mut coeffs) = (Vec::new(), Vec::new());
+        let mut products = Vec::new();
+        for (w, v) in weights.iter().zip(values) {
+            match (w, v) {
+                (Constant(w), Constant(v)) => const_sum += *w * v,
+                (Constant(c), x) | (x, Constant(c)) => {
+                    xs.push(x.clone());
+                    coeffs.push(Constant(*c));
+                }
+                _ => products.push((w.clone(), v.clone())),
+            }
+        }
+        if const_sum != F::zero() {
+            xs.push(Constant(const_sum));
+            coeffs.push(Constant(F::one()));
+        }
+        match (xs.is_empty(), products.is_empty()) {
+            (true, true) => self.load_constant(ctx, F::zero()),
+            (false, true) => self.inner_product(ctx, xs, coeffs),
+            (true, false) => {
+                let (a, b): (Vec<_>, Vec<_>) = products.into_iter().unzip();
+                self.inner_product(ctx, a, b)
+            }
+            (false, false) => {
+                let partial = self.inner_product(ctx, xs, coeffs);
+                // `Constant(1)` as the first `b` lets the accumulator start from `partial` directly
+                let (a, b): (Vec<_>, Vec<_>) =
+                    iter::once((Existing(&partial), Constant(F::one()))).chain(products).unzip();
+                self.inner_product(ctx, a, b)
+            }
+        }
+    }
+
+    /// Returns the random linear combination `sum_i xs[i] * r^i`, evaluated by Horner's rule with one `mul_add` gate
+    /// `| xs[i] | acc | r | xs[i] + acc * r |` per term after the first. Returns 0 for an empty slice.
+    ///
+    /// `r` is typically a verifier challenge, which is only available in phase 1 and later, so it is taken as an
+    /// assigned cell rather than a constant.
+    fn rlc<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        xs: &[AssignedValue<'v, F>],
+        r: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        match xs.split_last() {
+            Some((last, rest)) => rest.iter().rev().fold(last.clone(), |acc, x| {
+                self.mul_add(ctx, Existing(&acc), Existing(r), Existing(x))
+            }),
+            None => self.load_constant(ctx, F::zero()),
+        }
+    }
+
+    /// Returns `sum_i xs[i]^2`
+    ///
+    /// Each square costs one basic gate `| acc | x | x | acc + x^2 |`, the same as a general product in `inner_product`.
+    fn sum_of_squares<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        xs: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        self.inner_product(ctx, xs.iter().map(Existing), xs.iter().map(Existing))
+    }
+
+    /// Returns `base^exp` for a constant `exp`
+    ///
+    /// Uses one `mul` gate per step of `addition_chain(exp)`: a hardcoded shortest chain for exponents where
+    /// square-and-multiply is not optimal (15, 23, 27, 31), and left-to-right square-and-multiply otherwise.
+    /// `exp = 0` loads the constant 1 and `exp = 1` returns `base` without any new gates.
+    fn pow_const<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        base: &AssignedValue<'v, F>,
+        exp: u64,
+    ) -> AssignedValue<'v, F> {
+        if exp == 0 {
+            return self.load_constant(ctx, F::one());
+        }
+        let chain = addition_chain(exp);
+        let mut powers = vec![base.clone()];
+        for k in 1..chain.len() {
+            let (i, j) = (0..k)
+                .flat_map(|i| (i..k).map(move |j| (i, j)))
+                .find(|&(i, j)| chain[i] + chain[j] == chain[k])
+                .expect("not an addition chain");
+            let out = self.mul(ctx, Existing(&powers[i]), Existing(&powers[j]));
+            powers.push(out);
+        }
+        powers.pop().unwrap()
+    }
+
     /// very specialized for optimal range check, not for general consumption
     /// - `a_assigned` is expected to have capacity a.len()
     /// - we re-use `a_assigned` to save memory allocation
@@ -268,6 +522,22 @@ pub trait GateInstructions<F: ScalarField> {
         a_assigned: &mut Vec<AssignedValue<'b, F>>,
     ) -> AssignedValue<'b, F>;
 
+    /// Returns `(sum_i a[i] * b[i], a_cells)` where `a_cells` are the cells `a` was assigned to, e.g., so that a vector
+    /// can be reused with `Existing` in the next inner product of a matrix product instead of being assigned again.
+    ///
+    /// Allocating wrapper around `inner_product_left`.
+    fn inner_product_left_owned<'a, 'b: 'a>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
+        b: impl IntoIterator<Item = QuantumCell<'a, 'b, F>>,
+    ) -> (AssignedValue<'b, F>, Vec<AssignedValue<'b, F>>) {
+        let a = a.into_iter().collect::<Vec<_>>();
+        let mut a_cells = Vec::with_capacity(a.len());
+        let out = self.inner_product_left(ctx, a, b, &mut a_cells);
+        (out, a_cells)
+    }
+
     /// Returns an iterator with the partial sums `sum_{j=0..=i} a[j] * b[j]`.
     fn inner_product_with_sums<'a, 'b: 'a>(
         &self,
@@ -327,6 +597,26 @@ pub trait GateInstructions<F: ScalarField> {
         self.assign_region(ctx, cells, (0..len).map(|i| (3 * i as isize, None)))
     }
 
+    /// Returns the running products `[a_0, a_0 * a_1, ..., a_0 * ... * a_{n-1}]`
+    ///
+    /// The last element is the product of all of `a`
+    fn partial_products<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: &[AssignedValue<'v, F>],
+    ) -> Vec<AssignedValue<'v, F>> {
+        let mut prods = Vec::with_capacity(a.len());
+        let mut a = a.iter();
+        if let Some(first) = a.next() {
+            prods.push(first.clone());
+            for a in a {
+                let prod = self.mul(ctx, Existing(prods.last().unwrap()), Existing(a));
+                prods.push(prod);
+            }
+        }
+        prods
+    }
+
     // requires b.len() == a.len() + 1
     // returns
     // x_i = b_1 * (a_1...a_{i - 1})
@@ -400,6 +690,48 @@ pub trait GateInstructions<F: ScalarField> {
         self.sub(ctx, Constant(F::one()), a)
     }
 
+    /// Returns the XOR of all `bits`, i.e., 1 if an odd number of them are set, and 0 for an empty slice.
+    ///
+    /// Each bit is constrained to be boolean with `assert_bit`. Each step computes `acc ^ bit = acc + bit * (1 - 2 * acc)`
+    /// with two basic gates.
+    // | 1 | acc | -2 | 1 - 2acc | acc | bit | 1 - 2acc | acc ^ bit |
+    fn cumulative_xor<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        for bit in bits {
+            self.assert_bit(ctx, bit);
+        }
+        if bits.is_empty() {
+            return self.load_constant(ctx, F::zero());
+        }
+        let mut acc = bits[0].clone();
+        for bit in &bits[1..] {
+            let t_val = acc.value().map(|a| F::one() - F::from(2) * a);
+            let out_val = acc.value().zip(bit.value()).zip(t_val).map(|((a, b), t)| *a + *b * t);
+            let cells = vec![
+                Constant(F::one()),
+                Existing(&acc),
+                Constant(-F::from(2)),
+                Witness(t_val),
+                Existing(&acc),
+                Existing(bit),
+                Witness(t_val),
+                Witness(out_val),
+            ];
+            acc = self
+                .assign_region_smart(ctx, cells, vec![0, 4], vec![(3, 6)], vec![])
+                .pop()
+                .unwrap();
+        }
+        acc
+    }
+
+    /// Returns `sel ? a : b`, assuming `sel` is boolean.
+    ///
+    /// `a` and `b` may be `Constant`; with `GateStrategy::Vertical` a constant `b` (e.g., a default value) uses fewer advice cells
+    /// than loading it with `load_constant` first.
     fn select<'v>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -408,6 +740,42 @@ pub trait GateInstructions<F: ScalarField> {
         sel: QuantumCell<'_, 'v, F>,
     ) -> AssignedValue<'v, F>;
 
+    /// Returns `[a, b, c][idx]`.
+    ///
+    /// Decomposes `idx` into two bits with `num_to_bits` and constrains them not to both be set, so the circuit is
+    /// unsatisfiable unless `idx` is 0, 1, or 2. Then selects with two `select`s.
+    fn select3<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: QuantumCell<'_, 'v, F>,
+        b: QuantumCell<'_, 'v, F>,
+        c: QuantumCell<'_, 'v, F>,
+        idx: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let bits = self.num_to_bits(ctx, idx, 2);
+        let both = self.and(ctx, Existing(&bits[0]), Existing(&bits[1]));
+        self.assert_is_const(ctx, &both, F::zero());
+        let a_or_b = self.select(ctx, b, a, Existing(&bits[0]));
+        self.select(ctx, c, Existing(&a_or_b), Existing(&bits[1]))
+    }
+
+    /// Returns `[a, b, c, d][idx]`, constraining `idx` to be in `[0, 4)` by decomposing it into two bits with
+    /// `num_to_bits`, and selecting with three `select`s.
+    fn select4<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        a: QuantumCell<'_, 'v, F>,
+        b: QuantumCell<'_, 'v, F>,
+        c: QuantumCell<'_, 'v, F>,
+        d: QuantumCell<'_, 'v, F>,
+        idx: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let bits = self.num_to_bits(ctx, idx, 2);
+        let a_or_b = self.select(ctx, b, a, Existing(&bits[0]));
+        let c_or_d = self.select(ctx, d, c, Existing(&bits[0]));
+        self.select(ctx, Existing(&c_or_d), Existing(&a_or_b), Existing(&bits[1]))
+    }
+
     fn or_and<'v>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -548,6 +916,27 @@ pub trait GateInstructions<F: ScalarField> {
         self.assign_region_last(ctx, cells, (0..len).map(|i| (3 * i as isize, None)))
     }
 
+    /// Same as `select_by_indicator` but also constrains `indicator` to be one-hot,
+    /// i.e., every element is 0 or 1 and they sum to 1.
+    fn select_by_indicator_checked<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        values: &[AssignedValue<'v, F>],
+        indicator: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(values.len(), indicator.len());
+        for ind in indicator {
+            self.assert_bit(ctx, ind);
+        }
+        let ind_sum = self.inner_product(
+            ctx,
+            indicator.iter().map(Existing),
+            indicator.iter().map(|_| Constant(F::one())),
+        );
+        self.assert_is_const(ctx, &ind_sum, F::one());
+        self.select_by_indicator(ctx, values.iter().map(Existing), indicator)
+    }
+
     fn select_from_idx<'a, 'v: 'a>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -563,6 +952,109 @@ pub trait GateInstructions<F: ScalarField> {
         out
     }
 
+    /// Returns `values[idx]` where `idx = sum_i idx_bits[i] * 2^i` is given by its little-endian bits,
+    /// using a balanced binary tree of `select`s of depth `idx_bits.len()`.
+    ///
+    /// Assumes every element of `idx_bits` is 0 or 1; this is not constrained here.
+    fn mux_tree<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        values: &[AssignedValue<'v, F>],
+        idx_bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(
+            values.len(),
+            1 << idx_bits.len(),
+            "mux_tree: values.len() must be 2^idx_bits.len()"
+        );
+        let mut layer = values.to_vec();
+        for bit in idx_bits {
+            layer = layer
+                .chunks(2)
+                .map(|pair| self.select(ctx, Existing(&pair[1]), Existing(&pair[0]), Existing(bit)))
+                .collect();
+        }
+        layer.pop().unwrap()
+    }
+
+    /// Returns `values[idx]` by decomposing `idx` into `idx_bits` bits with `num_to_bits` and selecting with `mux_tree`.
+    ///
+    /// `values.len()` must be exactly `2^idx_bits` (pad with a default value otherwise), and `idx` must be less than
+    /// that, which the decomposition constrains. Costs `idx_bits` bit checks and `values.len() - 1` `select`s arranged
+    /// in a tree of depth `idx_bits`, instead of the indicator vector of `select_from_idx`.
+    fn select_from_sorted<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        values: &[AssignedValue<'v, F>],
+        idx: &AssignedValue<'v, F>,
+        idx_bits: usize,
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(
+            values.len(),
+            1 << idx_bits,
+            "select_from_sorted: values.len() must be 2^idx_bits"
+        );
+        let bits = self.num_to_bits(ctx, idx, idx_bits);
+        self.mux_tree(ctx, values, &bits)
+    }
+
+    /// Assigns the gates of `recorded` on `inputs` in a single region and returns its outputs.
+    ///
+    /// Every gate output is recomputed from `inputs`; see the `replay` module for what can be recorded.
+    fn replay<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        recorded: &RecordedGadget<F>,
+        inputs: &[AssignedValue<'v, F>],
+    ) -> Vec<AssignedValue<'v, F>> {
+        assert_eq!(inputs.len(), recorded.num_inputs);
+        let num_gates = recorded.gates.len();
+        let mut out_vals: Vec<Value<F>> = Vec::with_capacity(num_gates);
+        let mut cells = Vec::with_capacity(4 * num_gates);
+        let mut equality_offsets = Vec::new();
+        for (k, gate) in recorded.gates.iter().enumerate() {
+            let [a, b, c] = gate.map(|cell| match cell {
+                RecordedCell::Input(i) => inputs[i].value().copied(),
+                RecordedCell::Constant(c) => Value::known(c),
+                RecordedCell::Output(m) => out_vals[m],
+            });
+            for (j, cell) in gate.iter().enumerate() {
+                cells.push(match *cell {
+                    RecordedCell::Input(i) => Existing(&inputs[i]),
+                    RecordedCell::Constant(c) => Constant(c),
+                    RecordedCell::Output(m) => {
+                        // copy of the output of an earlier gate in this region
+                        equality_offsets.push((4 * m + 3, 4 * k + j));
+                        Witness(out_vals[m])
+                    }
+                });
+            }
+            let out = a.zip(b).zip(c).map(|((a, b), c)| a + b * c);
+            cells.push(Witness(out));
+            out_vals.push(out);
+        }
+        let assignments = if num_gates == 0 {
+            vec![]
+        } else {
+            self.assign_region_smart(
+                ctx,
+                cells,
+                (0..num_gates).map(|k| 4 * k),
+                equality_offsets,
+                vec![],
+            )
+        };
+        recorded
+            .outputs
+            .iter()
+            .map(|cell| match *cell {
+                RecordedCell::Input(i) => inputs[i].clone(),
+                RecordedCell::Constant(c) => self.load_constant(ctx, c),
+                RecordedCell::Output(k) => assignments[4 * k + 3].clone(),
+            })
+            .collect()
+    }
+
     // | out | a | inv | 1 | 0 | a | out | 0
     fn is_zero<'v>(
         &self,
@@ -594,6 +1086,98 @@ pub trait GateInstructions<F: ScalarField> {
         assigned_cells.into_iter().next().unwrap()
     }
 
+    /// Returns `is_zero(x)` for each `x` in `xs`
+    fn is_zero_vec<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        xs: &[AssignedValue<'v, F>],
+    ) -> Vec<AssignedValue<'v, F>> {
+        xs.iter().map(|x| self.is_zero(ctx, x)).collect()
+    }
+
+    /// Returns 1 if every element of `xs` is zero and 0 otherwise, as the product of `is_zero_vec(xs)`.
+    /// Returns 1 for an empty slice.
+    ///
+    /// `is_zero(sum_i xs[i]^2)` would be cheaper but is unsound: `-1` is a square in fields with `p = 1 mod 4`
+    /// such as BN254, so e.g. `[1, sqrt(-1)]` has a zero sum of squares.
+    fn all_zero<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        xs: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let is_zeros = self.is_zero_vec(ctx, xs);
+        let mut is_zeros = is_zeros.into_iter();
+        match is_zeros.next() {
+            Some(first) => {
+                is_zeros.fold(first, |acc, z| self.and(ctx, Existing(&acc), Existing(&z)))
+            }
+            None => self.load_constant(ctx, F::one()),
+        }
+    }
+
+    /// Returns 1 if some element of `xs` is zero and 0 otherwise, as `1 - prod_i (1 - is_zero(xs[i]))`.
+    /// Returns 0 for an empty slice.
+    fn any_zero<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        xs: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let is_zeros = self.is_zero_vec(ctx, xs);
+        let mut is_zeros = is_zeros.iter();
+        match is_zeros.next() {
+            Some(first) => {
+                let first = self.not(ctx, Existing(first));
+                // (1 - z) * acc
+                let none_zero =
+                    is_zeros.fold(first, |acc, z| self.mul_not(ctx, Existing(z), Existing(&acc)));
+                self.not(ctx, Existing(&none_zero))
+            }
+            None => self.load_constant(ctx, F::zero()),
+        }
+    }
+
+    /// Returns `bits[0] || ... || bits[n - 1]` as `1 - prod_i (1 - bits[i])` with a single accumulator chain.
+    /// Constrains every element of `bits` to be a bit. Returns 0 for an empty slice.
+    fn or_many<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        for bit in bits {
+            self.assert_bit(ctx, bit);
+        }
+        let mut bits = bits.iter();
+        match bits.next() {
+            Some(first) => {
+                let first = self.not(ctx, Existing(first));
+                // (1 - b) * acc
+                let none_set =
+                    bits.fold(first, |acc, b| self.mul_not(ctx, Existing(b), Existing(&acc)));
+                self.not(ctx, Existing(&none_set))
+            }
+            None => self.load_constant(ctx, F::zero()),
+        }
+    }
+
+    /// Returns `bits[0] && ... && bits[n - 1]` as `prod_i bits[i]` with a single accumulator chain.
+    /// Constrains every element of `bits` to be a bit. Returns 1 for an empty slice.
+    fn and_many<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        for bit in bits {
+            self.assert_bit(ctx, bit);
+        }
+        let mut bits = bits.iter();
+        match bits.next() {
+            Some(first) => {
+                bits.fold(first.clone(), |acc, b| self.and(ctx, Existing(&acc), Existing(b)))
+            }
+            None => self.load_constant(ctx, F::one()),
+        }
+    }
+
     fn is_equal<'v>(
         &self,
         ctx: &mut Context<'_, F>,
@@ -612,6 +1196,25 @@ pub trait GateInstructions<F: ScalarField> {
         range_bits: usize,
     ) -> Vec<AssignedValue<'v, F>>;
 
+    /// Returns `sum_i bits[i] * 2^i`, the number represented by the little-endian bit vector `bits`.
+    ///
+    /// Constrains each element of `bits` to be 0 or 1, so this is the constrained inverse of `num_to_bits`.
+    fn bits_to_num<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        assert!(bits.len() <= self.pow_of_two().len());
+        for bit in bits {
+            self.assert_bit(ctx, bit);
+        }
+        self.inner_product(
+            ctx,
+            bits.iter().map(Existing),
+            self.pow_of_two()[..bits.len()].iter().map(|c| Constant(*c)),
+        )
+    }
+
     /// given pairs `coords[i] = (x_i, y_i)`, let `f` be the unique degree `len(coords)` polynomial such that `f(x_i) = y_i` for all `i`.
     ///
     /// input: coords, x
@@ -657,6 +1260,84 @@ pub trait GateInstructions<F: ScalarField> {
         let out = self.mul(ctx, Existing(&eval.unwrap()), Existing(&z));
         (out, z)
     }
+
+    /// Given `values[i] = f(domain[i])` for the unique polynomial `f` of degree `< domain.len()`, returns `f(x)`.
+    ///
+    /// Uses the barycentric formula `f(x) = Prod_i (x - d_i) * sum_i w_i * v_i / (x - d_i)`
+    /// where the weights `w_i = 1 / Prod_{j != i} (d_i - d_j)` are constants since `domain` is fixed.
+    ///
+    /// If `x == domain[i]` then `values[i]` is returned.
+    ///
+    /// Assumes the elements of `domain` are distinct.
+    fn barycentric_eval<'v>(
+        &self,
+        ctx: &mut Context<'_, F>,
+        values: &[AssignedValue<'v, F>],
+        domain: &[F],
+        x: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        assert_eq!(values.len(), domain.len());
+        assert!(!domain.is_empty());
+        let weights = domain
+            .iter()
+            .enumerate()
+            .map(|(i, d_i)| {
+                domain
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(F::one(), |prod, (_, d_j)| prod * (*d_i - d_j))
+                    .invert()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let diffs =
+            domain.iter().map(|d| self.sub(ctx, Existing(x), Constant(*d))).collect::<Vec<_>>();
+        let is_zero = diffs.iter().map(|diff| self.is_zero(ctx, diff)).collect::<Vec<_>>();
+
+        // Prod_i (x - d_i) is zero exactly when x is in the domain
+        let mut z = diffs[0].clone();
+        for diff in diffs.iter().skip(1) {
+            z = self.mul(ctx, Existing(&z), Existing(diff));
+        }
+        // v_i / (x - d_i), where we divide by 1 instead if x == d_i
+        let mut quots = Vec::with_capacity(values.len());
+        for ((v, diff), is_zero) in values.iter().zip(diffs.iter()).zip(is_zero.iter()) {
+            let denom = self.add(ctx, Existing(diff), Existing(is_zero));
+            quots.push(self.div_unsafe(ctx, Existing(v), Existing(&denom)));
+        }
+        let sum =
+            self.inner_product(ctx, quots.iter().map(Existing), weights.into_iter().map(Constant));
+        let eval = self.mul(ctx, Existing(&sum), Existing(&z));
+
+        // if x == d_i then `eval` is zero and we add back `v_i`
+        let correction =
+            self.inner_product(ctx, values.iter().map(Existing), is_zero.iter().map(Existing));
+        self.add(ctx, Existing(&eval), Existing(&correction))
+    }
+}
+
+/// Returns an addition chain `1 = c_0 < c_1 < ... < c_n = exp` where each `c_k` is the sum of two earlier entries.
+/// Assumes `exp > 0`.
+fn addition_chain(exp: u64) -> Vec<u64> {
+    match exp {
+        15 => vec![1, 2, 3, 6, 12, 15],
+        23 => vec![1, 2, 3, 5, 10, 20, 23],
+        27 => vec![1, 2, 3, 6, 12, 24, 27],
+        31 => vec![1, 2, 3, 5, 10, 20, 30, 31],
+        _ => {
+            let mut chain = vec![1];
+            for i in (0..63 - exp.leading_zeros()).rev() {
+                let last = *chain.last().unwrap();
+                chain.push(2 * last);
+                if (exp >> i) & 1 == 1 {
+                    chain.push(2 * last + 1);
+                }
+            }
+            chain
+        }
+    }
 }
 
 pub trait RangeInstructions<F: ScalarField> {
@@ -667,6 +1348,10 @@ pub trait RangeInstructions<F: ScalarField> {
 
     fn lookup_bits(&self) -> usize;
 
+    /// Constrains `a` to have at most `range_bits` bits.
+    ///
+    /// Checking the same cell with the same `range_bits` more than once in a `Context` is free:
+    /// the memo is kept per-`Context`, so a new `Context` starts with no cells memoized.
     fn range_check<'a>(
         &self,
         ctx: &mut Context<'a, F>,
@@ -682,9 +1367,97 @@ pub trait RangeInstructions<F: ScalarField> {
         num_bits: usize,
     );
 
+    /// Constrains `a < b` by range checking `b - a - 1` to `bit_len` bits, so the circuit is unsatisfiable if `a >= b`.
+    ///
+    /// Assumes `a` and `b` have at most `bit_len` bits, where `bit_len` is much smaller than `F::NUM_BITS`,
+    /// so that `b - a - 1` wraps around to a value with more than `bit_len` bits whenever `a >= b`.
+    // | b - a - 1 | a | 1 | b - 1 | 1 | 1 | b |
+    fn assert_less_than<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        a: QuantumCell<'_, 'a, F>,
+        b: QuantumCell<'_, 'a, F>,
+        bit_len: usize,
+    ) {
+        let diff_val = b.value().zip(a.value()).map(|(b, a)| *b - a - F::one());
+        let b_minus_one_val = b.value().map(|b| *b - F::one());
+        let assigned = self.gate().assign_region(
+            ctx,
+            vec![
+                Witness(diff_val),
+                a,
+                Constant(F::one()),
+                Witness(b_minus_one_val),
+                Constant(F::one()),
+                Constant(F::one()),
+                b,
+            ],
+            vec![(0, None), (3, None)],
+        );
+        self.range_check(ctx, &assigned[0], bit_len);
+    }
+
+    /// Constrains `xs` to be non-decreasing, i.e., `xs[i] <= xs[i + 1]`, by range checking each `xs[i + 1] - xs[i]`
+    /// to `bit_len` bits. See `assert_strictly_sorted` for `<`.
+    ///
+    /// Assumes every element has at most `bit_len` bits, where `bit_len` is much smaller than `F::NUM_BITS`, so that a
+    /// decreasing pair wraps around to a difference with more than `bit_len` bits.
+    fn assert_sorted<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        xs: &[AssignedValue<'a, F>],
+        bit_len: usize,
+    ) {
+        for pair in xs.windows(2) {
+            let diff = self.gate().sub(ctx, Existing(&pair[1]), Existing(&pair[0]));
+            self.range_check(ctx, &diff, bit_len);
+        }
+    }
+
+    /// Constrains `xs` to be strictly increasing, i.e., `xs[i] < xs[i + 1]`, with `assert_less_than` on each adjacent
+    /// pair. Makes the same assumptions as `assert_sorted`.
+    fn assert_strictly_sorted<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        xs: &[AssignedValue<'a, F>],
+        bit_len: usize,
+    ) {
+        for pair in xs.windows(2) {
+            self.assert_less_than(ctx, Existing(&pair[0]), Existing(&pair[1]), bit_len);
+        }
+    }
+
+    /// Constrains `lo <= x <= hi`, after range checking `x` to `bit_len` bits.
+    ///
+    /// Assumes `lo <= hi < 2^bit_len`. Each bound is a `check_less_than` on `bit_len + 1` bits, so `hi + 1` fits.
+    fn assert_in_interval<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        lo: u64,
+        hi: u64,
+        bit_len: usize,
+    ) {
+        assert!(lo <= hi && bit_length(hi) <= bit_len);
+        self.range_check(ctx, x, bit_len);
+        // x < hi + 1
+        self.check_less_than(ctx, Existing(x), Constant(F::from(hi) + F::one()), bit_len + 1);
+        if lo > 0 {
+            // lo - 1 < x
+            self.check_less_than(ctx, Constant(F::from(lo - 1)), Existing(x), bit_len + 1);
+        }
+    }
+
+    /// Constrains `x` to be a printable ASCII byte, i.e., in `[0x20, 0x7e]`.
+    fn assert_printable_ascii<'a>(&self, ctx: &mut Context<'a, F>, x: &AssignedValue<'a, F>) {
+        self.assert_in_interval(ctx, x, 0x20, 0x7e, 8);
+    }
+
     /// Checks that `a` is in `[0, b)`.
     ///
     /// Does not require bit assumptions on `a, b` because we range check that `a` has at most `bit_length(b)` bits.
+    ///
+    /// The bound is exact, so `b` need not be a power of two, e.g., a ring size.
     fn check_less_than_safe<'a>(&self, ctx: &mut Context<'a, F>, a: &AssignedValue<'a, F>, b: u64) {
         let range_bits =
             (bit_length(b) + self.lookup_bits() - 1) / self.lookup_bits() * self.lookup_bits();
@@ -710,7 +1483,7 @@ pub trait RangeInstructions<F: ScalarField> {
         F: PrimeField,
     {
         let range_bits =
-            (b.bits() as usize + self.lookup_bits() - 1) / self.lookup_bits() * self.lookup_bits();
+            (biguint_bits(&b) + self.lookup_bits() - 1) / self.lookup_bits() * self.lookup_bits();
 
         self.range_check(ctx, a, range_bits);
         self.check_less_than(ctx, Existing(a), Constant(biguint_to_fe(&b)), range_bits)
@@ -727,6 +1500,20 @@ pub trait RangeInstructions<F: ScalarField> {
         num_bits: usize,
     ) -> AssignedValue<'a, F>;
 
+    /// Returns whether `x < c` for a constant `c`.
+    ///
+    /// Same as [`is_less_than`](Self::is_less_than) with a `Constant` right hand side, but `c` is folded into the fixed
+    /// column of a single addition, so it uses fewer advice cells.
+    ///
+    /// Warning: This may fail silently if `x` or `c` have more than `num_bits` bits
+    fn is_less_than_const<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        c: u64,
+        num_bits: usize,
+    ) -> AssignedValue<'a, F>;
+
     /// Returns whether `a` is in `[0, b)`.
     ///
     /// Does not require bit assumptions on `a, b` because we range check that `a` has at most `range_bits` bits.
@@ -756,12 +1543,163 @@ pub trait RangeInstructions<F: ScalarField> {
         F: PrimeField,
     {
         let range_bits =
-            (b.bits() as usize + self.lookup_bits() - 1) / self.lookup_bits() * self.lookup_bits();
+            (biguint_bits(&b) + self.lookup_bits() - 1) / self.lookup_bits() * self.lookup_bits();
 
         self.range_check(ctx, a, range_bits);
         self.is_less_than(ctx, Existing(a), Constant(biguint_to_fe(&b)), range_bits)
     }
 
+    /// Returns `(|x|, sign)` where `sign` is `1` if `x` is negative and `0` otherwise.
+    ///
+    /// Assumes the centered representation: `x` is a signed integer in `[-2^(bit_len - 1), 2^(bit_len - 1))`,
+    /// where a negative `x` is represented by the field element `p - |x|`.
+    /// This assumption is enforced by range checking `x + 2^(bit_len - 1)` to `bit_len` bits.
+    fn abs<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        bit_len: usize,
+    ) -> (AssignedValue<'a, F>, AssignedValue<'a, F>) {
+        assert!(bit_len > 0);
+        let half = self.gate().pow_of_two()[bit_len - 1];
+        let shifted = self.gate().add(ctx, Existing(x), Constant(half));
+        self.range_check(ctx, &shifted, bit_len);
+        // `x < 0` if and only if `x + 2^(bit_len - 1) < 2^(bit_len - 1)`
+        let sign = self.is_less_than(ctx, Existing(&shifted), Constant(half), bit_len);
+        let neg_x = self.gate().neg(ctx, Existing(x));
+        let abs = self.gate().select(ctx, Existing(&neg_x), Existing(x), Existing(&sign));
+        (abs, sign)
+    }
+
+    /// Returns the least significant bit of `x`, i.e., `1` if `x` is odd and `0` if it is even.
+    ///
+    /// The parity of a field element is only meaningful relative to its integer representative, so this needs the full
+    /// bit decomposition of `x` (which also constrains `x < 2^bit_len`); hence it lives on `RangeInstructions`.
+    fn lsb<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        bit_len: usize,
+    ) -> AssignedValue<'a, F> {
+        assert!(bit_len > 0);
+        self.gate().num_to_bits(ctx, x, bit_len).swap_remove(0)
+    }
+
+    /// Multiplies two unsigned fixed-point numbers scaled by `2^frac_bits`, returning `(a * b) >> frac_bits`
+    /// in the same scale.
+    ///
+    /// Rounding is by truncation of the low `frac_bits` bits of `a * b`, which for these non-negative values is the floor.
+    /// The truncated bits are range checked to `frac_bits` bits and the output to `int_bits + frac_bits` bits.
+    ///
+    /// Assumes `a` and `b` are less than `2^(int_bits + frac_bits)`, so `a * b` does not wrap around the modulus.
+    fn fixed_point_mul<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        a: &AssignedValue<'a, F>,
+        b: &AssignedValue<'a, F>,
+        frac_bits: usize,
+        int_bits: usize,
+    ) -> AssignedValue<'a, F>
+    where
+        F: PrimeField,
+    {
+        let total_bits = int_bits + frac_bits;
+        assert!(2 * total_bits < F::CAPACITY as usize);
+        let prod = self.gate().mul(ctx, Existing(a), Existing(b));
+        let (out, _) = self.div_mod(
+            ctx,
+            Existing(&prod),
+            BigUint::one().shl(frac_bits as u32),
+            2 * total_bits,
+        );
+        self.range_check(ctx, &out, total_bits);
+        out
+    }
+
+    /// Returns the `num_digits` little-endian base-`base` digits of `x`.
+    ///
+    /// Each digit is constrained to be `< base` and `sum_i digits[i] * base^i` is constrained to equal `x`,
+    /// so the circuit is unsatisfiable if `x >= base^num_digits`.
+    fn to_base_digits<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        base: u64,
+        num_digits: usize,
+    ) -> Vec<AssignedValue<'a, F>>
+    where
+        F: PrimeField,
+    {
+        assert!(base >= 2);
+        // `base^num_digits` must not overflow the field
+        assert!(bit_length(base) * num_digits <= F::CAPACITY as usize);
+        let digit_vals = x
+            .value()
+            .map(|x| {
+                let mut x = fe_to_biguint(x);
+                let base = BigUint::from(base);
+                (0..num_digits)
+                    .map(|_| {
+                        let (q, r) = x.div_rem(&base);
+                        x = q;
+                        biguint_to_fe(&r)
+                    })
+                    .collect::<Vec<F>>()
+            })
+            .transpose_vec(num_digits);
+        let digits = self.gate().assign_witnesses(ctx, digit_vals);
+        for digit in &digits {
+            self.check_less_than_safe(ctx, digit, base);
+        }
+        let mut pow = F::one();
+        let pows = (0..num_digits)
+            .map(|_| {
+                let c = Constant(pow);
+                pow *= F::from(base);
+                c
+            })
+            .collect::<Vec<_>>();
+        let sum = self.gate().inner_product(ctx, digits.iter().map(Existing), pows);
+        ctx.constrain_equal(&sum, x);
+        digits
+    }
+
+    /// Returns the `num_limbs` little-endian `limb_bits`-bit limbs of `x`.
+    ///
+    /// Each limb is range checked to `limb_bits` bits and `sum_i limbs[i] * 2^(limb_bits * i)` is constrained to
+    /// equal `x`, so this is the constrained counterpart of [`decompose`](crate::utils::decompose) and the circuit is
+    /// unsatisfiable if `x >= 2^(limb_bits * num_limbs)`.
+    fn decompose_le<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        num_limbs: usize,
+        limb_bits: usize,
+    ) -> Vec<AssignedValue<'a, F>>
+    where
+        F: PrimeField,
+    {
+        assert!(limb_bits * num_limbs <= F::CAPACITY as usize);
+        let limb_vals =
+            x.value().map(|x| decompose(x, num_limbs, limb_bits)).transpose_vec(num_limbs);
+        let limbs = self.gate().assign_witnesses(ctx, limb_vals);
+        for limb in &limbs {
+            self.range_check(ctx, limb, limb_bits);
+        }
+        let base = self.gate().pow_of_two()[limb_bits];
+        let mut pow = F::one();
+        let pows = (0..num_limbs)
+            .map(|_| {
+                let c = Constant(pow);
+                pow *= base;
+                c
+            })
+            .collect::<Vec<_>>();
+        let sum = self.gate().inner_product(ctx, limbs.iter().map(Existing), pows);
+        ctx.constrain_equal(&sum, x);
+        limbs
+    }
+
     /// Returns `(c, r)` such that `a = b * c + r`.
     ///
     /// Assumes that `b != 0`.
@@ -799,9 +1737,11 @@ pub trait RangeInstructions<F: ScalarField> {
         (assigned[2].clone(), assigned[0].clone())
     }
 
-    /// Returns `(c, r)` such that `a = b * c + r`.
+    /// Returns `(c, r)` such that `a = b * c + r` with `0 <= r < b`.
     ///
-    /// Assumes that `b != 0`.
+    /// The circuit is unsatisfiable when `b = 0`, since no `r` satisfies `r < b`.
+    /// A `Constant` divisor of zero panics instead.
+    /// Assumes `a` has at most `a_num_bits` bits and `b` at most `b_num_bits` bits.
     ///
     /// Let `X = 2 ** b_num_bits`.
     /// Write `a = a1 * X + a0` and `c = c1 * X + c0`.
@@ -822,7 +1762,24 @@ pub trait RangeInstructions<F: ScalarField> {
         a.value().map(|v| a_val = fe_to_biguint(v));
         let mut b_val = BigUint::one();
         b.value().map(|v| b_val = fe_to_biguint(v));
-        let (div, rem) = a_val.div_mod_floor(&b_val);
+        let b_assigned;
+        let b = match b {
+            Constant(c) => {
+                assert!(c != F::zero(), "div_mod_var: constant divisor is zero");
+                Constant(c)
+            }
+            Existing(b) => Existing(b),
+            b => {
+                b_assigned = self.gate().assign_region_last(ctx, vec![b], vec![]);
+                Existing(&b_assigned)
+            }
+        };
+        // a variable `b = 0` is rejected by `check_less_than(r, b)` below; witness `(0, a)` so that witness generation does not panic
+        let (div, rem) = if b_val.is_zero() {
+            (BigUint::zero(), a_val.clone())
+        } else {
+            a_val.div_mod_floor(&b_val)
+        };
         let x = BigUint::one().shl(b_num_bits as u32);
         let (div_hi, div_lo) = div.div_mod_floor(&x);
 
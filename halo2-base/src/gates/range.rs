@@ -64,8 +64,40 @@ impl<F: ScalarField> RangeConfig<F> {
         // params.k()
         circuit_degree: usize,
     ) -> Self {
-        assert!(lookup_bits <= 28);
         let lookup = meta.lookup_table_column();
+        Self::configure_with_lookup_table(
+            meta,
+            range_strategy,
+            num_advice,
+            num_lookup_advice,
+            num_fixed,
+            lookup,
+            lookup_bits,
+            context_id,
+            circuit_degree,
+        )
+    }
+
+    /// Same as `configure` but uses the existing table column `lookup` instead of creating a new one, so several
+    /// `RangeConfig`s, e.g., of different sub-gadgets, can share a single `lookup_bits` lookup table.
+    ///
+    /// Sharing contract:
+    /// * `lookup` is `config.lookup` of another `RangeConfig` with the same `lookup_bits`;
+    /// * `load_lookup_table` is called on exactly one of the configs sharing `lookup`;
+    /// * the configs sharing a `Context` have distinct `context_id`s.
+    pub fn configure_with_lookup_table(
+        meta: &mut ConstraintSystem<F>,
+        range_strategy: RangeStrategy,
+        num_advice: &[usize],
+        num_lookup_advice: &[usize],
+        num_fixed: usize,
+        lookup: TableColumn,
+        lookup_bits: usize,
+        context_id: usize,
+        // params.k()
+        circuit_degree: usize,
+    ) -> Self {
+        assert!(lookup_bits <= 28);
 
         let gate = FlexGateConfig::configure(
             meta,
@@ -176,10 +208,13 @@ impl<F: ScalarField> RangeConfig<F> {
     /// `offset` is only used if there is a single advice column
     fn enable_lookup<'a>(&self, ctx: &mut Context<'a, F>, acell: AssignedValue<'a, F>) {
         let phase = ctx.current_phase();
+        #[cfg(feature = "display")]
+        ctx.mark_constrained(&acell);
         if let Some(q) = &self.q_lookup[phase] {
+            ctx.count_lookup();
             q.enable(&mut ctx.region, acell.row()).expect("enable selector should not fail");
         } else {
-            ctx.cells_to_lookup.push(acell);
+            ctx.push_lookup_cell(acell);
         }
     }
 
@@ -220,7 +255,7 @@ impl<F: ScalarField> RangeConfig<F> {
                 ),
             };
             // the inner product above must equal `a`
-            ctx.region.constrain_equal(a.cell(), acc.cell());
+            ctx.constrain_equal(a, &acc);
         };
         assert_eq!(limbs_assigned.len(), k);
 
@@ -333,6 +368,10 @@ impl<F: ScalarField> RangeInstructions<F> for RangeConfig<F> {
         a: &AssignedValue<'a, F>,
         range_bits: usize,
     ) {
+        // the memo lives in `ctx`, so repeated checks are only skipped within the same `Context`
+        if !ctx.mark_range_checked(a, range_bits) {
+            return;
+        }
         let tmp = ctx.preallocated_vec_to_assign();
         self.range_check_limbs(ctx, a, range_bits, &mut tmp.as_ref().borrow_mut());
     }
@@ -434,4 +473,30 @@ impl<F: ScalarField> RangeInstructions<F> for RangeConfig<F> {
         let res = self.gate().is_zero(ctx, limbs.borrow().get(k).unwrap());
         res
     }
+
+    fn is_less_than_const<'a>(
+        &self,
+        ctx: &mut Context<'a, F>,
+        x: &AssignedValue<'a, F>,
+        c: u64,
+        num_bits: usize,
+    ) -> AssignedValue<'a, F> {
+        let k = (num_bits + self.lookup_bits - 1) / self.lookup_bits;
+        let padded_bits = k * self.lookup_bits;
+        let pow_padded = self.gate.pow_of_two[padded_bits];
+
+        // x - c + 2^padded_bits with a single `add`, since `2^padded_bits - c` is a constant
+        let shifted_cell =
+            self.gate.add(ctx, Existing(x), Constant(pow_padded - self.gate.get_field_element(c)));
+
+        let limbs = ctx.preallocated_vec_to_assign();
+        self.range_check_limbs(
+            ctx,
+            &shifted_cell,
+            padded_bits + self.lookup_bits,
+            &mut limbs.borrow_mut(),
+        );
+        let res = self.gate().is_zero(ctx, limbs.borrow().get(k).unwrap());
+        res
+    }
 }
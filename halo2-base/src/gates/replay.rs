@@ -0,0 +1,71 @@
+//! Record the gate pattern of a gadget once and replay it on many inputs.
+//!
+//! A [`RecordedGadget`] is a straight-line program of basic gates `a + b * c = out`, built symbolically with
+//! [`RecordedGadget::mul_add`] and friends. [`GateInstructions::replay`](super::GateInstructions::replay) then
+//! assigns the whole program in a single region with fresh witness values, which avoids re-running the Rust code
+//! that built the gadget for every instance.
+//!
+//! Only gadgets whose every witness is the output of a basic gate can be recorded:
+//! * there is no data-dependent control flow, since the same gates are replayed regardless of the input values;
+//! * there are no "hint" witnesses (e.g., an inverse or a bit decomposition) that are computed off-circuit and only
+//! checked by the gate, since replay recomputes each output as `a + b * c` from the gate's other cells.
+use crate::utils::ScalarField;
+
+/// A cell of a [`RecordedGadget`]: a gadget input, a constant, or the output of an earlier gate
+#[derive(Clone, Copy, Debug)]
+pub enum RecordedCell<F: ScalarField> {
+    Input(usize),
+    Constant(F),
+    Output(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedGadget<F: ScalarField> {
+    pub num_inputs: usize,
+    /// `gates[k] = [a, b, c]` is the basic gate `| a | b | c | a + b * c |`, whose output is `RecordedCell::Output(k)`
+    pub gates: Vec<[RecordedCell<F>; 3]>,
+    /// the cells returned by `replay`, in order
+    pub outputs: Vec<RecordedCell<F>>,
+}
+
+impl<F: ScalarField> RecordedGadget<F> {
+    pub fn new(num_inputs: usize) -> Self {
+        Self { num_inputs, gates: Vec::new(), outputs: Vec::new() }
+    }
+
+    pub fn input(&self, i: usize) -> RecordedCell<F> {
+        assert!(i < self.num_inputs, "input index out of bounds");
+        RecordedCell::Input(i)
+    }
+
+    /// Records `a * b + c`
+    pub fn mul_add(
+        &mut self,
+        a: RecordedCell<F>,
+        b: RecordedCell<F>,
+        c: RecordedCell<F>,
+    ) -> RecordedCell<F> {
+        self.gates.push([c, a, b]);
+        RecordedCell::Output(self.gates.len() - 1)
+    }
+
+    /// Records `a + b`
+    pub fn add(&mut self, a: RecordedCell<F>, b: RecordedCell<F>) -> RecordedCell<F> {
+        self.mul_add(b, RecordedCell::Constant(F::one()), a)
+    }
+
+    /// Records `a - b`
+    pub fn sub(&mut self, a: RecordedCell<F>, b: RecordedCell<F>) -> RecordedCell<F> {
+        self.mul_add(b, RecordedCell::Constant(-F::one()), a)
+    }
+
+    /// Records `a * b`
+    pub fn mul(&mut self, a: RecordedCell<F>, b: RecordedCell<F>) -> RecordedCell<F> {
+        self.mul_add(a, b, RecordedCell::Constant(F::zero()))
+    }
+
+    /// Marks `a` as the next output of the gadget
+    pub fn output(&mut self, a: RecordedCell<F>) {
+        self.outputs.push(a);
+    }
+}
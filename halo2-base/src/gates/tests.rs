@@ -1,5 +1,6 @@
 use super::{
-    flex_gate::{FlexGateConfig, GateStrategy},
+    flex_gate::{FlexGateConfig, GateOpCounts, GateStrategy},
+    lookup_table::LookupTableConfig,
     range, GateInstructions, RangeInstructions,
 };
 use crate::halo2_proofs::{circuit::*, dev::MockProver, halo2curves::bn256::Fr, plonk::*};
@@ -8,6 +9,7 @@ use crate::{
     QuantumCell::{Constant, Existing, Witness},
     SKIP_FIRST_PASS,
 };
+use std::rc::Rc;
 
 #[derive(Default)]
 struct MyCircuit<F> {
@@ -88,6 +90,16 @@ impl Circuit<Fr> for MyCircuit<Fr> {
                     config.mul(ctx, Existing(&c_cell), Existing(&b_cell));
                 }
 
+                // test partial_products
+                {
+                    let cells = [a_cell.clone(), b_cell.clone(), c_cell.clone()];
+                    let prods = config.partial_products(ctx, &cells);
+                    let expected = [self.a, self.a * self.b, self.a * self.b * self.c];
+                    for (prod, expected) in prods.iter().zip(expected) {
+                        prod.value().zip(expected).assert_if_known(|(a, b)| **a == *b);
+                    }
+                }
+
                 // test idx_to_indicator
                 {
                     config.idx_to_indicator(ctx, Constant(Fr::from(3u64)), 4);
@@ -142,6 +154,795 @@ fn plot_gates() {
     halo2_proofs::dev::CircuitLayout::default().render(k, &circuit, &root).unwrap();
 }
 
+type GateTestFn = Rc<dyn Fn(&FlexGateConfig<Fr>, &mut Context<'_, Fr>)>;
+
+/// Runs a single closure against a `FlexGateConfig` so small gadgets can be tested without writing a new circuit
+struct GateTestCircuit {
+    test: GateTestFn,
+}
+
+const GATE_TEST_K: u32 = 10;
+
+impl Circuit<Fr> for GateTestCircuit {
+    type Config = FlexGateConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { test: self.test.clone() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        FlexGateConfig::configure(
+            meta,
+            GateStrategy::Vertical,
+            &[NUM_ADVICE],
+            1,
+            0,
+            GATE_TEST_K as usize,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let mut first_pass = SKIP_FIRST_PASS;
+
+        layouter.assign_region(
+            || "gate test",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: config.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: config.constants.clone(),
+                    },
+                );
+                (self.test)(&config, &mut aux);
+                Ok(())
+            },
+        )
+    }
+}
+
+fn gate_test_prover(
+    test: impl Fn(&FlexGateConfig<Fr>, &mut Context<'_, Fr>) + 'static,
+) -> MockProver<Fr> {
+    MockProver::run(GATE_TEST_K, &GateTestCircuit { test: Rc::new(test) }, vec![]).unwrap()
+}
+
+#[test]
+fn test_select_by_indicator_checked() {
+    for idx in 0..4usize {
+        let prover = gate_test_prover(move |gate, ctx| {
+            let values =
+                gate.assign_witnesses(ctx, (0..4u64).map(|i| Value::known(Fr::from(10 + i))));
+            let indicator = gate
+                .assign_witnesses(ctx, (0..4).map(|i| Value::known(Fr::from((i == idx) as u64))));
+            let out = gate.select_by_indicator_checked(ctx, &values, &indicator);
+            ctx.constrain_equal(&out, &values[idx]);
+        });
+        prover.assert_satisfied();
+    }
+    // indicator that is not one-hot
+    let prover = gate_test_prover(|gate, ctx| {
+        let values = gate.assign_witnesses(ctx, (0..4u64).map(|i| Value::known(Fr::from(10 + i))));
+        let indicator =
+            gate.assign_witnesses(ctx, [1u64, 1, 0, 0].map(|i| Value::known(Fr::from(i))));
+        gate.select_by_indicator_checked(ctx, &values, &indicator);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_assert_in_set() {
+    let set = [3u64, 5, 7].map(Fr::from);
+    let prover = gate_test_prover(move |gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(5)));
+        gate.assert_in_set(ctx, &x, &set);
+    });
+    prover.assert_satisfied();
+
+    let prover = gate_test_prover(move |gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(4)));
+        gate.assert_in_set(ctx, &x, &set);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_public_cells_order() {
+    gate_test_prover(|gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, (0..3u64).map(|i| Value::known(Fr::from(i))));
+        for i in [2, 0, 1] {
+            ctx.mark_public(&cells[i]);
+        }
+        let rows = ctx.public_cells().iter().map(|cell| cell.row()).collect::<Vec<_>>();
+        assert_eq!(rows, [2, 0, 1].map(|i| cells[i].row()));
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_assign_public() {
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(7u64)));
+        ctx.mark_public(&x);
+        for i in 1..4usize {
+            let (cell, index) = gate.assign_public(ctx, Value::known(Fr::from(i as u64)));
+            assert_eq!(index, i);
+            assert_eq!(ctx.public_cells()[index].row(), cell.row());
+        }
+        assert_eq!(ctx.public_cells().len(), 4);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_lincomb() {
+    let coeffs = [3u64, 0, 5, 1].map(Fr::from);
+    let xs = [7u64, 11, 2, 9].map(Fr::from);
+    let expected = coeffs.iter().zip(xs.iter()).fold(Fr::zero(), |acc, (c, x)| acc + *c * x);
+    gate_test_prover(move |gate, ctx| {
+        let x_cells = gate.assign_witnesses(ctx, xs.map(Value::known));
+        let out = gate.lincomb(ctx, &coeffs, &x_cells);
+        gate.assert_is_const(ctx, &out, expected);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_assert_not_zero() {
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(5)));
+        gate.assert_not_zero(ctx, &x);
+    })
+    .assert_satisfied();
+
+    let prover = gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::zero()));
+        gate.assert_not_zero(ctx, &x);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_inner_product_mixed() {
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(2)));
+        let a = [
+            Constant(Fr::from(4)),
+            Existing(&x),
+            Constant(Fr::from(3)),
+            Witness(Value::known(Fr::from(5))),
+        ];
+        let b = [
+            Constant(Fr::from(10)),
+            Constant(Fr::from(7)),
+            Witness(Value::known(Fr::from(6))),
+            Witness(Value::known(Fr::from(11))),
+        ];
+        let row_before = ctx.advice_alloc[gate.context_id()].1;
+        let out = gate.inner_product(ctx, a.iter().cloned(), b.iter().cloned());
+        let const_cells = ctx.advice_alloc[gate.context_id()].1 - row_before;
+        // 4 * 10 + 2 * 7 + 3 * 6 + 5 * 11
+        gate.assert_is_const(ctx, &out, Fr::from(127));
+
+        let a = [
+            Witness(Value::known(Fr::from(4))),
+            Existing(&x),
+            Constant(Fr::from(3)),
+            Witness(Value::known(Fr::from(5))),
+        ];
+        let row_before = ctx.advice_alloc[gate.context_id()].1;
+        let out = gate.inner_product(ctx, a.iter().cloned(), b.iter().cloned());
+        let var_cells = ctx.advice_alloc[gate.context_id()].1 - row_before;
+        gate.assert_is_const(ctx, &out, Fr::from(127));
+
+        // the constant term 4 * 10 is folded into the starting value
+        assert_eq!(const_cells, 10);
+        assert_eq!(var_cells, 13);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_mux_tree() {
+    for idx in 0..8u64 {
+        gate_test_prover(move |gate, ctx| {
+            let values =
+                gate.assign_witnesses(ctx, (0..8u64).map(|i| Value::known(Fr::from(10 + i))));
+            let idx_bits =
+                gate.assign_witnesses(ctx, (0..3).map(|i| Value::known(Fr::from((idx >> i) & 1))));
+            let out = gate.mux_tree(ctx, &values, &idx_bits);
+            ctx.constrain_equal(&out, &values[idx as usize]);
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_replay() {
+    use super::replay::RecordedGadget;
+
+    // (x, y) -> (x^2 + x * y, x - y + 5)
+    let mut recorded = RecordedGadget::new(2);
+    let (x, y) = (recorded.input(0), recorded.input(1));
+    let xx = recorded.mul(x, x);
+    let out0 = recorded.mul_add(x, y, xx);
+    let diff = recorded.sub(x, y);
+    let out1 = recorded.add(diff, super::replay::RecordedCell::Constant(Fr::from(5u64)));
+    recorded.output(out0);
+    recorded.output(out1);
+
+    gate_test_prover(move |gate, ctx| {
+        for (x, y) in [(3u64, 4u64), (10, 2), (0, 0)] {
+            let inputs = gate.assign_witnesses(ctx, [x, y].map(|v| Value::known(Fr::from(v))));
+            let outs = gate.replay(ctx, &recorded, &inputs);
+            gate.assert_is_const(ctx, &outs[0], Fr::from(x * x + x * y));
+            gate.assert_is_const(ctx, &outs[1], Fr::from(x + 5) - Fr::from(y));
+        }
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_sum_of_squares() {
+    let xs = [3u64, 0, 7, 12, 1];
+    let expected: u64 = xs.iter().map(|x| x * x).sum();
+    gate_test_prover(move |gate, ctx| {
+        let xs = gate.assign_witnesses(ctx, xs.map(|x| Value::known(Fr::from(x))));
+        let out = gate.sum_of_squares(ctx, &xs);
+        gate.assert_is_const(ctx, &out, Fr::from(expected));
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_select_constant_branch() {
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(7u64)));
+        let c = Fr::from(100u64);
+        for sel in [0u64, 1] {
+            // expected outputs of `select(x, c)`, `select(c, x)`, `select(c, 5)`
+            let expected =
+                if sel == 1 { [Fr::from(7u64), c, c] } else { [c, Fr::from(7u64), Fr::from(5u64)] };
+            let sel = gate.load_witness(ctx, Value::known(Fr::from(sel)));
+
+            let row = ctx.advice_alloc[gate.context_id()].1;
+            let out = gate.select(ctx, Existing(&x), Constant(c), Existing(&sel));
+            let cells = ctx.advice_alloc[gate.context_id()].1 - row;
+            gate.assert_is_const(ctx, &out, expected[0]);
+
+            let row = ctx.advice_alloc[gate.context_id()].1;
+            let loaded = gate.load_constant(ctx, c);
+            gate.select(ctx, Existing(&x), Existing(&loaded), Existing(&sel));
+            assert!(cells < ctx.advice_alloc[gate.context_id()].1 - row);
+
+            let out = gate.select(ctx, Constant(c), Existing(&x), Existing(&sel));
+            gate.assert_is_const(ctx, &out, expected[1]);
+            let out = gate.select(ctx, Constant(c), Constant(Fr::from(5u64)), Existing(&sel));
+            gate.assert_is_const(ctx, &out, expected[2]);
+        }
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_when_known() {
+    gate_test_prover(|_, ctx| {
+        assert!(ctx.witness_gen());
+        assert_eq!(crate::utils::value_to_option(ctx.when_known(|| 5u64)), Some(5));
+        ctx.set_witness_gen(false);
+        let value = ctx.when_known(|| -> u64 { panic!("should not run during keygen") });
+        assert_eq!(crate::utils::value_to_option(value), None);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_pow_const() {
+    use crate::halo2_proofs::arithmetic::Field;
+    let base = Fr::from(3u64);
+    gate_test_prover(move |gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(base));
+        for exp in (0..=10u64).chain([15, 23, 27, 31]) {
+            let out = gate.pow_const(ctx, &x, exp);
+            gate.assert_is_const(ctx, &out, base.pow_vartime([exp]));
+        }
+    })
+    .assert_satisfied();
+
+    // 15 = 1, 2, 3, 6, 12, 15 takes 5 multiplications instead of 6 with square-and-multiply
+    gate_test_prover(move |gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(base));
+        let start = ctx.advice_alloc[gate.context_id()].1;
+        gate.pow_const(ctx, &x, 15);
+        assert_eq!(ctx.advice_alloc[gate.context_id()].1 - start, 5 * 4);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_cumulative_xor() {
+    for (bits, expected) in [
+        (vec![1u64, 0, 1, 1], 1u64),
+        (vec![1, 1, 0, 0], 0),
+        (vec![1], 1),
+        (vec![0, 0], 0),
+        (vec![], 0),
+    ] {
+        gate_test_prover(move |gate, ctx| {
+            let bits = gate.assign_witnesses(ctx, bits.iter().map(|b| Value::known(Fr::from(*b))));
+            let parity = gate.cumulative_xor(ctx, &bits);
+            gate.assert_is_const(ctx, &parity, Fr::from(expected));
+        })
+        .assert_satisfied();
+    }
+    // non-boolean inputs are rejected
+    let prover = gate_test_prover(|gate, ctx| {
+        let bits = gate.assign_witnesses(ctx, [1u64, 2].map(|b| Value::known(Fr::from(b))));
+        gate.cumulative_xor(ctx, &bits);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_strict_mode_known() {
+    gate_test_prover(|gate, ctx| {
+        ctx.strict_mode(true);
+        let a = gate.load_witness(ctx, Value::known(Fr::from(3u64)));
+        gate.mul(ctx, Existing(&a), Constant(Fr::from(2u64)));
+    })
+    .assert_satisfied();
+}
+
+#[test]
+#[should_panic(expected = "strict mode: unknown witness")]
+fn test_strict_mode_unknown() {
+    gate_test_prover(|gate, ctx| {
+        ctx.strict_mode(true);
+        gate.load_witness(ctx, Value::unknown());
+    });
+}
+
+#[test]
+fn test_weighted_sum_mixed() {
+    gate_test_prover(|gate, ctx| {
+        let [a, b, c] = [5u64, 6, 11].map(|x| gate.load_witness(ctx, Value::known(Fr::from(x))));
+        let weights = [
+            Constant(Fr::from(2u64)),
+            Witness(Value::known(Fr::from(3u64))),
+            Existing(&a),
+            Constant(Fr::from(4u64)),
+            Constant(Fr::from(7u64)),
+        ];
+        let values = [
+            Existing(&b),
+            Existing(&c),
+            Constant(Fr::from(3u64)),
+            Constant(Fr::from(10u64)),
+            Witness(Value::known(Fr::one())),
+        ];
+        // 2 * 6 + 3 * 11 + 5 * 3 + 4 * 10 + 7 * 1
+        let out = gate.weighted_sum(ctx, &weights, &values);
+        gate.assert_is_const(ctx, &out, Fr::from(107u64));
+
+        let only_consts =
+            gate.weighted_sum(ctx, &[Constant(Fr::from(4u64))], &[Constant(Fr::from(10u64))]);
+        gate.assert_is_const(ctx, &only_consts, Fr::from(40u64));
+        let empty = gate.weighted_sum(ctx, &[], &[]);
+        gate.assert_is_const(ctx, &empty, Fr::zero());
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_estimate_rows() {
+    gate_test_prover(|gate, ctx| {
+        let [a, b, c, d] =
+            [2u64, 3, 0, 7].map(|x| gate.load_witness(ctx, Value::known(Fr::from(x))));
+        let start = ctx.advice_alloc[gate.context_id()].1;
+        let sum = gate.add(ctx, Existing(&a), Existing(&b));
+        let prod = gate.mul(ctx, Existing(&sum), Existing(&c));
+        gate.assert_bit(ctx, &c);
+        gate.select(ctx, Existing(&a), Existing(&b), Existing(&c));
+        gate.is_zero(ctx, &prod);
+        gate.is_equal(ctx, Existing(&a), Existing(&d));
+        gate.inner_product(ctx, [&a, &b, &c, &d].map(Existing), [&d, &c, &b, &a].map(Existing));
+        let used = ctx.advice_alloc[gate.context_id()].1 - start;
+
+        let ops = GateOpCounts {
+            basic: 3,
+            select: 1,
+            is_zero: 1,
+            is_equal: 1,
+            inner_products: 1,
+            inner_product_terms: 4,
+        };
+        assert_eq!(ops.advice_cells(), used);
+        assert_eq!(gate.estimate_rows(&ops), (used + NUM_ADVICE - 1) / NUM_ADVICE);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_is_zero_vec() {
+    for (xs, all_zero, any_zero) in [
+        (vec![0u64, 5, 0, 7], 0u64, 1u64),
+        (vec![0, 0, 0], 1, 1),
+        (vec![3, 1, 4], 0, 0),
+        (vec![0], 1, 1),
+        (vec![], 1, 0),
+    ] {
+        gate_test_prover(move |gate, ctx| {
+            let cells = gate.assign_witnesses(ctx, xs.iter().map(|x| Value::known(Fr::from(*x))));
+            let is_zeros = gate.is_zero_vec(ctx, &cells);
+            for (is_zero, x) in is_zeros.iter().zip(xs.iter()) {
+                gate.assert_is_const(ctx, is_zero, Fr::from((*x == 0) as u64));
+            }
+            let all = gate.all_zero(ctx, &cells);
+            gate.assert_is_const(ctx, &all, Fr::from(all_zero));
+            let any = gate.any_zero(ctx, &cells);
+            gate.assert_is_const(ctx, &any, Fr::from(any_zero));
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_all_zero_one_nonzero() {
+    use crate::utils::fe_sqrt;
+    // nonzero vectors whose sum of squares vanishes must not be reported as all zero
+    let i = fe_sqrt(&-Fr::one()).unwrap();
+    for xs in [vec![Fr::one(), i], vec![Fr::zero(), i, Fr::one(), Fr::zero()], vec![-Fr::one()]] {
+        gate_test_prover(move |gate, ctx| {
+            let cells = gate.assign_witnesses(ctx, xs.iter().map(|x| Value::known(*x)));
+            let all = gate.all_zero(ctx, &cells);
+            gate.assert_is_const(ctx, &all, Fr::zero());
+        })
+        .assert_satisfied();
+    }
+    gate_test_prover(|gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, [Value::known(Fr::zero()); 8]);
+        let all = gate.all_zero(ctx, &cells);
+        gate.assert_is_const(ctx, &all, Fr::one());
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_current_phase() {
+    gate_test_prover(|gate, ctx| {
+        assert_eq!(ctx.current_phase(), 0);
+        gate.load_witness(ctx, Value::known(Fr::from(1u64)));
+        ctx.next_phase();
+        assert_eq!(ctx.current_phase(), 1);
+        ctx.next_phase();
+        assert_eq!(ctx.current_phase(), 2);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_rlc() {
+    let r = Fr::from(1_000_003u64);
+    for xs in [vec![3u64, 1, 4, 1, 5], vec![7], vec![]] {
+        // host-side sum_i xs[i] * r^i
+        let expected = xs.iter().rev().fold(Fr::zero(), |acc, x| acc * r + Fr::from(*x));
+        gate_test_prover(move |gate, ctx| {
+            let cells = gate.assign_witnesses(ctx, xs.iter().map(|x| Value::known(Fr::from(*x))));
+            let r = gate.load_witness(ctx, Value::known(r));
+            let start = ctx.advice_alloc[gate.context_id()].1;
+            let out = gate.rlc(ctx, &cells, &r);
+            if cells.len() > 1 {
+                assert_eq!(ctx.advice_alloc[gate.context_id()].1 - start, 4 * (cells.len() - 1));
+            }
+            gate.assert_is_const(ctx, &out, expected);
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_select_from_sorted() {
+    let values = [2u64, 3, 5, 7, 11, 13, 17, 19];
+    for idx in 0..values.len() as u64 {
+        gate_test_prover(move |gate, ctx| {
+            let cells = gate.assign_witnesses(ctx, values.map(|v| Value::known(Fr::from(v))));
+            let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(idx)));
+            let out = gate.select_from_sorted(ctx, &cells, &idx_cell, 3);
+            gate.assert_is_const(ctx, &out, Fr::from(values[idx as usize]));
+        })
+        .assert_satisfied();
+    }
+    // an index out of range does not decompose into 3 bits
+    let prover = gate_test_prover(move |gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, values.map(|v| Value::known(Fr::from(v))));
+        let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(8u64)));
+        gate.select_from_sorted(ctx, &cells, &idx_cell, 3);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_constrain_equal_to_consts() {
+    let vals = [0x80u64, 0, 0, 0, 1];
+    gate_test_prover(move |gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, vals.map(|v| Value::known(Fr::from(v))));
+        gate.constrain_equal_to_consts(ctx, &cells, &vals.map(Fr::from));
+    })
+    .assert_satisfied();
+
+    let prover = gate_test_prover(move |gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, vals.map(|v| Value::known(Fr::from(v))));
+        gate.constrain_equal_to_consts(ctx, &cells, &[0x80u64, 0, 1, 0, 1].map(Fr::from));
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+#[should_panic(expected = "cells and consts must have the same length")]
+fn test_constrain_equal_to_consts_length_mismatch() {
+    gate_test_prover(|gate, ctx| {
+        let cells = gate.assign_witnesses(ctx, [1u64, 2].map(|v| Value::known(Fr::from(v))));
+        gate.constrain_equal_to_consts(ctx, &cells, &[Fr::from(1u64)]);
+    });
+}
+
+#[cfg(feature = "display")]
+#[test]
+fn test_assigned_to_string() {
+    use crate::utils::assigned_to_string;
+    // the unknown value case is tested on `Value` in utils, since MockProver needs known witnesses
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(42u64)));
+        assert_eq!(assigned_to_string(&x), format!("col=0 row={} val=0x2a", x.row()));
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_select3() {
+    let vals = [10u64, 20, 30, 40];
+    for idx in 0..3u64 {
+        gate_test_prover(move |gate, ctx| {
+            let [a, b, _] = [vals[0], vals[1], vals[2]]
+                .map(|v| gate.load_witness(ctx, Value::known(Fr::from(v))));
+            let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(idx)));
+            // `c` may also be a constant
+            let out = gate.select3(
+                ctx,
+                Existing(&a),
+                Existing(&b),
+                Constant(Fr::from(vals[2])),
+                &idx_cell,
+            );
+            gate.assert_is_const(ctx, &out, Fr::from(vals[idx as usize]));
+        })
+        .assert_satisfied();
+    }
+    for idx in 0..4u64 {
+        gate_test_prover(move |gate, ctx| {
+            let [a, b, c, d] = vals.map(|v| gate.load_witness(ctx, Value::known(Fr::from(v))));
+            let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(idx)));
+            let out = gate.select4(
+                ctx,
+                Existing(&a),
+                Existing(&b),
+                Existing(&c),
+                Existing(&d),
+                &idx_cell,
+            );
+            gate.assert_is_const(ctx, &out, Fr::from(vals[idx as usize]));
+        })
+        .assert_satisfied();
+    }
+    // 3 is out of range for `select3`, and 4 for `select4`
+    let prover = gate_test_prover(move |gate, ctx| {
+        let [a, b, c] =
+            [vals[0], vals[1], vals[2]].map(|v| gate.load_witness(ctx, Value::known(Fr::from(v))));
+        let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(3u64)));
+        gate.select3(ctx, Existing(&a), Existing(&b), Existing(&c), &idx_cell);
+    });
+    assert!(prover.verify().is_err());
+    let prover = gate_test_prover(move |gate, ctx| {
+        let [a, b, c, d] = vals.map(|v| gate.load_witness(ctx, Value::known(Fr::from(v))));
+        let idx_cell = gate.load_witness(ctx, Value::known(Fr::from(4u64)));
+        gate.select4(ctx, Existing(&a), Existing(&b), Existing(&c), Existing(&d), &idx_cell);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_inner_product_prefix() {
+    // the last 3 entries of `a` are zero padding
+    let a = [5u64, 7, 11, 13, 0, 0, 0];
+    let b = [2u64, 3, 4, 5, 6, 7, 8];
+    gate_test_prover(move |gate, ctx| {
+        let a = gate.assign_witnesses(ctx, a.map(|x| Value::known(Fr::from(x))));
+        let b = gate.assign_witnesses(ctx, b.map(|x| Value::known(Fr::from(x))));
+        let start = ctx.advice_alloc[gate.context_id()].1;
+        let full = gate.inner_product(ctx, a.iter().map(Existing), b.iter().map(Existing));
+        let mid = ctx.advice_alloc[gate.context_id()].1;
+        let prefix =
+            gate.inner_product_prefix(ctx, a.iter().map(Existing), b.iter().map(Existing), 4);
+        let end = ctx.advice_alloc[gate.context_id()].1;
+        assert!(end - mid < mid - start);
+        ctx.constrain_equal(&full, &prefix);
+        gate.assert_is_const(ctx, &prefix, Fr::from(5 * 2 + 7 * 3 + 11 * 4 + 13 * 5));
+    })
+    .assert_satisfied();
+}
+
+#[test]
+#[should_panic(expected = "exceeds vector lengths")]
+fn test_inner_product_prefix_short_b() {
+    gate_test_prover(|gate, ctx| {
+        let a = gate.assign_witnesses(ctx, [5u64, 7, 11].map(|x| Value::known(Fr::from(x))));
+        let b = gate.assign_witnesses(ctx, [2u64, 3].map(|x| Value::known(Fr::from(x))));
+        gate.inner_product_prefix(ctx, a.iter().map(Existing), b.iter().map(Existing), 3);
+    });
+}
+
+#[test]
+fn test_inner_product_left_owned() {
+    // [[1, 2, 3], [4, 5, 6]] * [7, 8, 9] = [50, 122]
+    gate_test_prover(|gate, ctx| {
+        let v = [7u64, 8, 9].map(|x| Value::known(Fr::from(x)));
+        let (row0, v_cells) = gate.inner_product_left_owned(
+            ctx,
+            v.map(Witness),
+            [1u64, 2, 3].map(|c| Constant(Fr::from(c))),
+        );
+        assert_eq!(v_cells.len(), 3);
+        // the second row reuses the cells of `v` through copy constraints
+        let row1 = gate.inner_product(
+            ctx,
+            v_cells.iter().map(Existing),
+            [4u64, 5, 6].map(|c| Constant(Fr::from(c))),
+        );
+        gate.assert_is_const(ctx, &row0, Fr::from(50u64));
+        gate.assert_is_const(ctx, &row1, Fr::from(122u64));
+        for (cell, x) in v_cells.iter().zip([7u64, 8, 9]) {
+            gate.assert_is_const(ctx, cell, Fr::from(x));
+        }
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_or_many_and_many() {
+    for (bits, or, and) in [
+        (vec![], 0u64, 1u64),
+        (vec![0u64], 0, 0),
+        (vec![1], 1, 1),
+        (vec![0, 0, 0, 0], 0, 0),
+        (vec![1, 1, 1, 1], 1, 1),
+        (vec![0, 1, 0, 0], 1, 0),
+        (vec![1, 1, 0, 1], 1, 0),
+    ] {
+        gate_test_prover(move |gate, ctx| {
+            let bits = gate.assign_witnesses(ctx, bits.iter().map(|b| Value::known(Fr::from(*b))));
+            let or_out = gate.or_many(ctx, &bits);
+            let and_out = gate.and_many(ctx, &bits);
+            gate.assert_is_const(ctx, &or_out, Fr::from(or));
+            gate.assert_is_const(ctx, &and_out, Fr::from(and));
+        })
+        .assert_satisfied();
+    }
+    // non-boolean inputs are rejected
+    let prover = gate_test_prover(|gate, ctx| {
+        let bits = gate.assign_witnesses(ctx, [0u64, 2, 0].map(|b| Value::known(Fr::from(b))));
+        gate.or_many(ctx, &bits);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_is_boolean() {
+    for (x, expected) in [(Fr::zero(), 1u64), (Fr::one(), 1), (Fr::from(2), 0), (-Fr::one(), 0)] {
+        gate_test_prover(move |gate, ctx| {
+            let x = gate.load_witness(ctx, Value::known(x));
+            let is_bool = gate.is_boolean(ctx, &x);
+            gate.assert_is_const(ctx, &is_bool, Fr::from(expected));
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_constrain_equal_if() {
+    for (a, b, flag, ok) in [
+        (5u64, 5u64, 1u64, true),
+        (5, 6, 1, false),
+        (5, 6, 0, true),
+        (5, 5, 0, true),
+        (5, 5, 2, false),
+    ] {
+        let prover = gate_test_prover(move |gate, ctx| {
+            let [a, b, flag] =
+                [a, b, flag].map(|x| gate.load_witness(ctx, Value::known(Fr::from(x))));
+            gate.constrain_equal_if(ctx, &a, &b, &flag);
+        });
+        assert_eq!(prover.verify().is_ok(), ok, "a = {a}, b = {b}, flag = {flag}");
+    }
+}
+
+#[test]
+fn test_row_offset() {
+    for hand_written in [3u64, 4] {
+        let prover = gate_test_prover(move |gate, ctx| {
+            ctx.set_row_offset(5);
+            let x = gate.load_witness(ctx, Value::known(Fr::from(3)));
+            let y = gate.add(ctx, Existing(&x), Constant(Fr::one()));
+            assert_eq!(x.row(), 5);
+            assert!(y.row() > x.row());
+            gate.assert_is_const(ctx, &y, Fr::from(4));
+
+            // a hand-written assignment in a reserved row, copy constrained to a gadget cell
+            let column = gate.basic_gates[0][0].value;
+            let cell = ctx.assign_cell(
+                Witness(Value::known(Fr::from(hand_written))),
+                column,
+                #[cfg(feature = "display")]
+                gate.context_id(),
+                0,
+                #[cfg(feature = "halo2-pse")]
+                0,
+            );
+            ctx.constrain_equal(&cell, &x);
+        });
+        assert_eq!(prover.verify().is_ok(), hand_written == 3);
+    }
+}
+
+#[cfg(feature = "display")]
+#[test]
+fn test_unconstrained_cells() {
+    gate_test_prover(|gate, ctx| {
+        ctx.track_unconstrained_cells(true);
+        // `x` is never used again, while `y` is copied into an `add` gate
+        let _x = gate.load_witness(ctx, Value::known(Fr::from(3)));
+        let y = gate.load_witness(ctx, Value::known(Fr::from(4)));
+        gate.add(ctx, Existing(&y), Constant(Fr::one()));
+        assert_eq!(ctx.unconstrained_cells(), vec![0]);
+    })
+    .assert_satisfied();
+}
+
+#[cfg(feature = "display")]
+#[test]
+fn test_annotate() {
+    gate_test_prover(|gate, ctx| {
+        let x = gate.load_witness(ctx, Value::known(Fr::from(3)));
+        // not annotated
+        gate.add(ctx, Existing(&x), Constant(Fr::one()));
+        let row = ctx.advice_alloc[gate.context_id()].1;
+        ctx.annotate("y = x * x");
+        let y = gate.mul(ctx, Existing(&x), Existing(&x));
+        ctx.annotate("y == 9");
+        gate.assert_is_const(ctx, &y, Fr::from(9));
+        // a pending note without a following constraint is not exported
+        ctx.annotate("dangling");
+
+        let annotations = ctx.export_annotated();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].0 .1, row);
+        assert_eq!(annotations[0].1, "y = x * x");
+        assert_eq!(annotations[1].0, (y.cell().column.index(), y.row()));
+        assert_eq!(annotations[1].1, "y == 9");
+    })
+    .assert_satisfied();
+}
+
 #[derive(Default)]
 struct RangeTestCircuit<F> {
     range_bits: usize,
@@ -257,6 +1058,12 @@ impl Circuit<Fr> for RangeTestCircuit<Fr> {
                 {
                     config.gate().is_zero(ctx, &a);
                 }
+                // test num_to_bits -> bits_to_num round trip
+                {
+                    let bits = config.gate().num_to_bits(ctx, &a, self.range_bits);
+                    let num = config.gate().bits_to_num(ctx, &bits);
+                    ctx.constrain_equal(&a, &num);
+                }
 
                 config.finalize(ctx);
 
@@ -307,6 +1114,548 @@ fn plot_range() {
     halo2_proofs::dev::CircuitLayout::default().render(7, &circuit, &root).unwrap();
 }
 
+type RangeTestFn = Rc<dyn Fn(&range::RangeConfig<Fr>, &mut Context<'_, Fr>)>;
+
+/// Runs a single closure against a `RangeConfig` so small range gadgets can be tested without writing a new circuit
+struct RangeHarnessCircuit {
+    test: RangeTestFn,
+}
+
+const RANGE_TEST_K: u32 = 11;
+
+impl Circuit<Fr> for RangeHarnessCircuit {
+    type Config = range::RangeConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { test: self.test.clone() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        range::RangeConfig::configure(
+            meta,
+            range::RangeStrategy::Vertical,
+            &[NUM_ADVICE],
+            &[1],
+            1,
+            3,
+            0,
+            RANGE_TEST_K as usize,
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        config.load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+
+        layouter.assign_region(
+            || "range test",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: config.gate.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: config.gate.constants.clone(),
+                    },
+                );
+                (self.test)(&config, &mut aux);
+                config.finalize(&mut aux);
+                Ok(())
+            },
+        )
+    }
+}
+
+fn range_test_prover(
+    test: impl Fn(&range::RangeConfig<Fr>, &mut Context<'_, Fr>) + 'static,
+) -> MockProver<Fr> {
+    MockProver::run(RANGE_TEST_K, &RangeHarnessCircuit { test: Rc::new(test) }, vec![]).unwrap()
+}
+
+/// Two `RangeConfig`s with distinct context ids sharing the lookup table of the first one
+struct SharedRangeTableCircuit {
+    a: u64,
+    b: u64,
+}
+
+impl Circuit<Fr> for SharedRangeTableCircuit {
+    type Config = (range::RangeConfig<Fr>, range::RangeConfig<Fr>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { a: self.a, b: self.b }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let range0 = range::RangeConfig::configure(
+            meta,
+            range::RangeStrategy::Vertical,
+            &[NUM_ADVICE],
+            &[1],
+            1,
+            3,
+            0,
+            RANGE_TEST_K as usize,
+        );
+        let range1 = range::RangeConfig::configure_with_lookup_table(
+            meta,
+            range::RangeStrategy::Vertical,
+            &[NUM_ADVICE],
+            &[1],
+            1,
+            range0.lookup,
+            3,
+            1,
+            RANGE_TEST_K as usize,
+        );
+        (range0, range1)
+    }
+
+    fn synthesize(
+        &self,
+        (range0, range1): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        // the table is loaded once for both configs
+        range0.load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+
+        layouter.assign_region(
+            || "shared range table",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: range0.gate.max_rows,
+                        num_context_ids: 2,
+                        fixed_columns: range0.gate.constants.clone(),
+                    },
+                );
+                let ctx = &mut aux;
+                let a = range0.gate().load_witness(ctx, Value::known(Fr::from(self.a)));
+                range0.range_check(ctx, &a, 8);
+                let b = range1.gate().load_witness(ctx, Value::known(Fr::from(self.b)));
+                range1.range_check(ctx, &b, 8);
+                // `cells_to_lookup` is shared by the `Context`, so a single `finalize` copies the cells of both configs
+                range0.finalize(ctx);
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_shared_range_lookup_table() {
+    let run = |a: u64, b: u64| {
+        MockProver::run(RANGE_TEST_K, &SharedRangeTableCircuit { a, b }, vec![]).unwrap()
+    };
+    run(200, 255).assert_satisfied();
+    assert!(run(256, 3).verify().is_err());
+    assert!(run(3, 256).verify().is_err());
+
+    let mut meta = ConstraintSystem::<Fr>::default();
+    let (range0, range1) = SharedRangeTableCircuit::configure(&mut meta);
+    assert_eq!(range0.lookup, range1.lookup);
+}
+
+/// Looks up each of `inputs` in a squaring table of `0..8` and checks the outputs against `expected`
+struct SquareLookupCircuit {
+    inputs: Vec<u64>,
+    expected: Vec<u64>,
+}
+
+impl Circuit<Fr> for SquareLookupCircuit {
+    type Config = (FlexGateConfig<Fr>, LookupTableConfig<Fr>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self { inputs: self.inputs.clone(), expected: self.expected.clone() }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let gate = FlexGateConfig::configure(
+            meta,
+            GateStrategy::Vertical,
+            &[NUM_ADVICE],
+            1,
+            0,
+            GATE_TEST_K as usize,
+        );
+        (gate, LookupTableConfig::configure(meta, 1))
+    }
+
+    fn synthesize(
+        &self,
+        (gate, lookup): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let entries = (0..8u64).map(|x| (Fr::from(x), Fr::from(x * x))).collect::<Vec<_>>();
+        let table = lookup.load_lookup_table(&mut layouter, &entries)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+
+        layouter.assign_region(
+            || "square lookup",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: gate.max_rows,
+                        num_context_ids: 2,
+                        fixed_columns: gate.constants.clone(),
+                    },
+                );
+                let ctx = &mut aux;
+                for (x, y) in self.inputs.iter().zip(self.expected.iter()) {
+                    let x = gate.load_witness(ctx, Value::known(Fr::from(*x)));
+                    let out = lookup.lookup(ctx, &table, &x);
+                    gate.assert_is_const(ctx, &out, Fr::from(*y));
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[test]
+fn test_function_lookup() {
+    let run = |inputs: Vec<u64>, expected: Vec<u64>| {
+        MockProver::run(GATE_TEST_K, &SquareLookupCircuit { inputs, expected }, vec![]).unwrap()
+    };
+    run(vec![3, 0, 7, 3], vec![9, 0, 49, 9]).assert_satisfied();
+    // 8 is not in the table
+    assert!(run(vec![8], vec![0]).verify().is_err());
+}
+
+#[test]
+fn test_abs() {
+    // (x, |x|, sign)
+    for (x, abs, sign) in [
+        (Fr::from(5u64), 5u64, 0u64),
+        (-Fr::from(5u64), 5, 1),
+        (Fr::zero(), 0, 0),
+        (-Fr::from(128u64), 128, 1),
+    ] {
+        range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(x));
+            let (out_abs, out_sign) = range.abs(ctx, &x, 8);
+            range.gate().assert_is_const(ctx, &out_abs, Fr::from(abs));
+            range.gate().assert_is_const(ctx, &out_sign, Fr::from(sign));
+        })
+        .assert_satisfied();
+    }
+    // 200 is not in [-128, 128)
+    let prover = range_test_prover(|range, ctx| {
+        let x = range.gate().load_witness(ctx, Value::known(Fr::from(200u64)));
+        range.abs(ctx, &x, 8);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_lsb() {
+    for (x, bit) in [(100u64, 0u64), (101, 1), (0, 0), (255, 1)] {
+        range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            let lsb = range.lsb(ctx, &x, 8);
+            range.gate().assert_is_const(ctx, &lsb, Fr::from(bit));
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_fixed_point_mul() {
+    const FRAC_BITS: usize = 8;
+    const INT_BITS: usize = 8;
+    // 3.5 * 2.25, 1.1015625 * 1.30078125 (truncated), 0 * 255.99609375, 15.5 * 15.5
+    for (a, b) in [(896u64, 576u64), (282, 333), (0, 65535), (3968, 3968)] {
+        let expected = (a * b) >> FRAC_BITS;
+        range_test_prover(move |range, ctx| {
+            let a = range.gate().load_witness(ctx, Value::known(Fr::from(a)));
+            let b = range.gate().load_witness(ctx, Value::known(Fr::from(b)));
+            let out = range.fixed_point_mul(ctx, &a, &b, FRAC_BITS, INT_BITS);
+            range.gate().assert_is_const(ctx, &out, Fr::from(expected));
+        })
+        .assert_satisfied();
+    }
+}
+
+#[test]
+fn test_assert_less_than() {
+    let prover = |a: u64, b: u64| {
+        range_test_prover(move |range, ctx| {
+            let [a, b] = [a, b].map(|x| range.gate().load_witness(ctx, Value::known(Fr::from(x))));
+            range.assert_less_than(ctx, Existing(&a), Existing(&b), 8);
+        })
+    };
+    prover(3, 200).assert_satisfied();
+    prover(0, 1).assert_satisfied();
+    assert!(prover(100, 100).verify().is_err());
+    assert!(prover(101, 100).verify().is_err());
+}
+
+#[test]
+fn test_to_base_digits() {
+    for (x, base, num_digits) in
+        [(123456789u64, 10u64, 9usize), (123456789, 10, 12), (1000, 7, 4), (0, 3, 2)]
+    {
+        // host-side base conversion
+        let mut expected = vec![];
+        let mut rest = x;
+        for _ in 0..num_digits {
+            expected.push(rest % base);
+            rest /= base;
+        }
+        range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            let digits = range.to_base_digits(ctx, &x, base, num_digits);
+            for (digit, expected) in digits.iter().zip(expected.iter()) {
+                range.gate().assert_is_const(ctx, digit, Fr::from(*expected));
+            }
+        })
+        .assert_satisfied();
+    }
+    // 1000 does not fit in 3 decimal digits
+    let prover = range_test_prover(|range, ctx| {
+        let x = range.gate().load_witness(ctx, Value::known(Fr::from(1000u64)));
+        range.to_base_digits(ctx, &x, 10, 3);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_decompose_le() {
+    let x = 0x1234_5678_9abcu64;
+    range_test_prover(move |range, ctx| {
+        let x_cell = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+        let limbs = range.decompose_le(ctx, &x_cell, 4, 12);
+        let pows = (0..4).map(|i| Constant(range.gate().pow_of_two()[12 * i]));
+        let recomposed = range.gate().inner_product(ctx, limbs.iter().map(Existing), pows);
+        ctx.constrain_equal(&recomposed, &x_cell);
+        for (i, limb) in limbs.iter().enumerate() {
+            range.gate().assert_is_const(ctx, limb, Fr::from((x >> (12 * i)) & 0xfff));
+        }
+    })
+    .assert_satisfied();
+    // 2^48 does not fit in 4 limbs of 12 bits
+    let prover = range_test_prover(|range, ctx| {
+        let x = range.gate().load_witness(ctx, Value::known(Fr::from(1u64 << 48)));
+        range.decompose_le(ctx, &x, 4, 12);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_assert_in_interval() {
+    for x in [0x20u64, 0x41, 0x7e] {
+        range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            range.assert_printable_ascii(ctx, &x);
+        })
+        .assert_satisfied();
+    }
+    for x in [0x1fu64, 0x7f, 0x120] {
+        let prover = range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            range.assert_printable_ascii(ctx, &x);
+        });
+        assert!(prover.verify().is_err());
+    }
+    // the whole `bit_len`-bit range, and a single point
+    for (x, lo, hi, ok) in
+        [(0u64, 0u64, 255u64, true), (255, 0, 255, true), (9, 9, 9, true), (10, 9, 9, false)]
+    {
+        let prover = range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            range.assert_in_interval(ctx, &x, lo, hi, 8);
+        });
+        assert_eq!(prover.verify().is_ok(), ok);
+    }
+}
+
+#[test]
+fn test_is_less_than_const() {
+    let c = 20u64;
+    for (x, expected) in [(c - 1, 1u64), (c, 0), (c + 1, 0)] {
+        let prover = range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            let row = ctx.advice_alloc[range.gate().context_id()].1;
+            let out = range.is_less_than_const(ctx, &x, c, 5);
+            let const_cells = ctx.advice_alloc[range.gate().context_id()].1 - row;
+            range.gate().assert_is_const(ctx, &out, Fr::from(expected));
+
+            let row = ctx.advice_alloc[range.gate().context_id()].1;
+            let out = range.is_less_than(ctx, Existing(&x), Constant(Fr::from(c)), 5);
+            let var_cells = ctx.advice_alloc[range.gate().context_id()].1 - row;
+            range.gate().assert_is_const(ctx, &out, Fr::from(expected));
+
+            assert!(const_cells < var_cells);
+        });
+        prover.assert_satisfied();
+    }
+}
+
+#[test]
+fn test_check_less_than_safe_non_power_of_two() {
+    // a ring size that is not a power of two, and the boundary cases of a power of two
+    for (x, n, ok) in [
+        (4u64, 5u64, true),
+        (5, 5, false),
+        (1000, 1000, false),
+        (999, 1000, true),
+        (0, 1, true),
+        (1, 1, false),
+        (255, 256, true),
+        (256, 256, false),
+        (1 << 20, 1000, false),
+    ] {
+        let prover = range_test_prover(move |range, ctx| {
+            let x = range.gate().load_witness(ctx, Value::known(Fr::from(x)));
+            range.check_less_than_safe(ctx, &x, n);
+        });
+        assert_eq!(prover.verify().is_ok(), ok, "x = {x}, n = {n}");
+    }
+}
+
+#[test]
+fn test_div_mod_var() {
+    use rand::Rng;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let (a_bits, b_bits) = (20, 9);
+    let mut cases = (0..5)
+        .map(|_| (rng.gen_range(0..1u64 << a_bits), rng.gen_range(1..1u64 << b_bits)))
+        .collect::<Vec<_>>();
+    cases.extend([(0, 1), ((1 << a_bits) - 1, 1), ((1 << a_bits) - 1, (1 << b_bits) - 1), (5, 7)]);
+    for (a, b) in cases {
+        let (q, r) = (a / b, a % b);
+        range_test_prover(move |range, ctx| {
+            let a = range.gate().load_witness(ctx, Value::known(Fr::from(a)));
+            let b = range.gate().load_witness(ctx, Value::known(Fr::from(b)));
+            let (q_cell, r_cell) =
+                range.div_mod_var(ctx, Existing(&a), Existing(&b), a_bits, b_bits);
+            range.gate().assert_is_const(ctx, &q_cell, Fr::from(q));
+            range.gate().assert_is_const(ctx, &r_cell, Fr::from(r));
+        })
+        .assert_satisfied();
+    }
+    // a constant divisor
+    range_test_prover(move |range, ctx| {
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(100u64)));
+        let (q_cell, r_cell) =
+            range.div_mod_var(ctx, Existing(&a), Constant(Fr::from(7u64)), a_bits, b_bits);
+        range.gate().assert_is_const(ctx, &q_cell, Fr::from(14u64));
+        range.gate().assert_is_const(ctx, &r_cell, Fr::from(2u64));
+    })
+    .assert_satisfied();
+    // a zero variable divisor makes the circuit unsatisfiable instead of panicking
+    let prover = range_test_prover(move |range, ctx| {
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(12u64)));
+        range.div_mod_var(ctx, Existing(&a), Witness(Value::known(Fr::zero())), a_bits, b_bits);
+    });
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+#[should_panic(expected = "constant divisor is zero")]
+fn test_div_mod_var_zero_constant() {
+    range_test_prover(|range, ctx| {
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(12u64)));
+        range.div_mod_var(ctx, Existing(&a), Constant(Fr::zero()), 20, 9);
+    });
+}
+
+#[test]
+fn test_assert_sorted() {
+    // (xs, sorted, strictly sorted)
+    for (xs, sorted, strict) in [
+        (vec![1u64, 5, 9, 200], true, true),
+        (vec![3, 3, 7], true, false),
+        (vec![4, 2, 8], false, false),
+        (vec![255, 0], false, false),
+        (vec![6], true, true),
+    ] {
+        let xs_clone = xs.clone();
+        let prover = range_test_prover(move |range, ctx| {
+            let cells =
+                range.gate().assign_witnesses(ctx, xs.iter().map(|x| Value::known(Fr::from(*x))));
+            range.assert_sorted(ctx, &cells, 8);
+        });
+        assert_eq!(prover.verify().is_ok(), sorted);
+
+        let prover = range_test_prover(move |range, ctx| {
+            let cells = range
+                .gate()
+                .assign_witnesses(ctx, xs_clone.iter().map(|x| Value::known(Fr::from(*x))));
+            range.assert_strictly_sorted(ctx, &cells, 8);
+        });
+        assert_eq!(prover.verify().is_ok(), strict);
+    }
+}
+
+#[test]
+fn test_range_check_memo() {
+    range_test_prover(|range, ctx| {
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(100u64)));
+        range.range_check(ctx, &a, 8);
+        let (lookups, rows) = (ctx.cells_to_lookup.len(), ctx.advice_alloc[0].1);
+        // same cell and bits: nothing new is assigned
+        range.range_check(ctx, &a, 8);
+        assert_eq!((ctx.cells_to_lookup.len(), ctx.advice_alloc[0].1), (lookups, rows));
+        // different bits: checked again
+        range.range_check(ctx, &a, 9);
+        assert!(ctx.cells_to_lookup.len() > lookups);
+    })
+    .assert_satisfied();
+}
+
+#[test]
+fn test_lookup_count() {
+    range_test_prover(|range, ctx| {
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(100u64)));
+        assert_eq!(ctx.lookup_count(), 0);
+        // 9 bits with `lookup_bits = 3` is 3 limbs
+        range.range_check(ctx, &a, 9);
+        assert_eq!(ctx.lookup_count(), 3);
+        // cells queued directly for the lookup column are counted too
+        let b = range.gate().load_witness(ctx, Value::known(Fr::from(5u64)));
+        ctx.push_lookup_cell(b);
+        assert_eq!(ctx.lookup_count(), 4);
+    })
+    .assert_satisfied();
+}
+
+#[cfg(all(feature = "display", debug_assertions))]
+#[test]
+#[should_panic(expected = "LOOKUP BUDGET")]
+fn test_lookup_budget() {
+    range_test_prover(|range, ctx| {
+        ctx.set_lookup_budget(2);
+        let a = range.gate().load_witness(ctx, Value::known(Fr::from(100u64)));
+        range.range_check(ctx, &a, 9);
+    });
+}
+
 mod lagrange {
     use crate::halo2_proofs::{
         arithmetic::Field,
@@ -461,3 +1810,100 @@ mod lagrange {
         Ok(())
     }
 }
+
+mod barycentric {
+    use super::*;
+    use crate::halo2_proofs::arithmetic::Field;
+
+    #[derive(Default)]
+    struct MyCircuit<F> {
+        values: Vec<Value<F>>,
+        domain: Vec<F>,
+        x: Value<F>,
+        expected: Value<F>,
+    }
+
+    impl Circuit<Fr> for MyCircuit<Fr> {
+        type Config = FlexGateConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: self.values.iter().map(|_| Value::unknown()).collect(),
+                domain: self.domain.clone(),
+                x: Value::unknown(),
+                expected: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            FlexGateConfig::configure(meta, GateStrategy::Vertical, &[NUM_ADVICE], 1, 0, 10)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let mut first_pass = SKIP_FIRST_PASS;
+
+            layouter.assign_region(
+                || "gate",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: config.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: config.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+
+                    let values = config.assign_witnesses(ctx, self.values.clone());
+                    let x = config.load_witness(ctx, self.x);
+                    let eval = config.barycentric_eval(ctx, &values, &self.domain, &x);
+                    config.assert_equal(ctx, Existing(&eval), Witness(self.expected));
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    // Lagrange interpolation computed off-circuit
+    fn lagrange_eval(values: &[Fr], domain: &[Fr], x: Fr) -> Fr {
+        values.iter().zip(domain.iter()).enumerate().fold(Fr::zero(), |acc, (i, (v, d_i))| {
+            let (num, denom) = domain
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold((Fr::one(), Fr::one()), |(num, denom), (_, d_j)| {
+                    (num * (x - d_j), denom * (*d_i - d_j))
+                });
+            acc + *v * num * denom.invert().unwrap()
+        })
+    }
+
+    #[test]
+    fn test_barycentric_eval() {
+        let k = 10;
+        let domain = [1u64, 3, 4, 7].map(Fr::from).to_vec();
+        let values = [5u64, 11, 2, 9].map(Fr::from).to_vec();
+        for x in [Fr::from(100u64), domain[2]] {
+            let circuit = MyCircuit::<Fr> {
+                values: values.iter().map(|v| Value::known(*v)).collect(),
+                domain: domain.clone(),
+                x: Value::known(x),
+                expected: Value::known(lagrange_eval(&values, &domain, x)),
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+}
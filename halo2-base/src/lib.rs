@@ -34,11 +34,11 @@ use halo2_proofs::{
     circuit::{AssignedCell, Cell, Region, Value},
     plonk::{Advice, Assigned, Column, Fixed},
 };
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 #[cfg(feature = "halo2-pse")]
 use std::marker::PhantomData;
 use std::{cell::RefCell, rc::Rc};
-use utils::ScalarField;
+use utils::{value_to_option, ScalarField};
 
 pub mod gates;
 // pub mod hashes;
@@ -202,10 +202,35 @@ pub struct Context<'a, F: ScalarField> {
     pub zero_cell: Option<AssignedValue<'a, F>>,
 
     // `cells_to_lookup` is a vector keeping track of all cells that we want to enable lookup for. When there is more than 1 advice column we will copy_advice all of these cells to the single lookup enabled column and do lookups there
-    pub cells_to_lookup: Vec<AssignedValue<'a, F>>,
+    // Only push to it through `push_lookup_cell`, so every lookup is counted against the lookup budget
+    pub(crate) cells_to_lookup: Vec<AssignedValue<'a, F>>,
+
+    // `witness_gen` is whether this `Context` is generating witnesses (proving or mock proving), as opposed to keygen;
+    // it gates the closures passed to `when_known`
+    witness_gen: bool,
+
+    // `strict` makes `assign_cell` panic on unknown witness values, to catch witness generation bugs while proving
+    strict: bool,
+
+    // `num_lookups` counts the cells looked up in this `Context` so far, across all phases
+    num_lookups: usize,
+    // `lookup_budget` is the maximum allowed `num_lookups`, only enforced in debug builds
+    #[cfg(feature = "display")]
+    lookup_budget: Option<usize>,
+
+    // `range_checked` memoizes the `(column index, row, range_bits)` of cells already range checked in this `Context`,
+    // so `RangeInstructions::range_check` does not emit duplicate lookups for the same cell
+    range_checked: FxHashSet<(usize, usize, usize)>,
+
+    // `public_cells` keeps track of the cells marked as public, in order, to be constrained equal to the instance column by the circuit
+    public_cells: Vec<AssignedValue<'a, F>>,
 
     current_phase: usize,
 
+    // `row_offset` is the first row `Context` assigns to in each of its advice and fixed columns,
+    // rows above it are left to hand-written assignments in the same region
+    row_offset: usize,
+
     #[cfg(feature = "display")]
     pub op_count: FxHashMap<String, usize>,
     #[cfg(feature = "display")]
@@ -214,6 +239,21 @@ pub struct Context<'a, F: ScalarField> {
     pub total_lookup_cells: [usize; MAX_PHASE],
     #[cfg(feature = "display")]
     pub total_fixed: usize,
+    // `track_unconstrained` turns on recording `advice_log` and `constrained_advice` for `unconstrained_cells`;
+    // off by default since it costs memory proportional to the number of advice cells
+    #[cfg(feature = "display")]
+    track_unconstrained: bool,
+    // `advice_log` records the `(column index, row)` of every advice cell assigned through `assign_cell`, in order
+    #[cfg(feature = "display")]
+    advice_log: Vec<(usize, usize)>,
+    // `constrained_advice` is the set of `(column index, row)` advice positions seen in a gate, copy constraint, or lookup
+    #[cfg(feature = "display")]
+    constrained_advice: FxHashSet<(usize, usize)>,
+    // `pending_annotation` is the note passed to `annotate`, waiting for the next constraint to be emitted
+    #[cfg(feature = "display")]
+    pending_annotation: Option<String>,
+    #[cfg(feature = "display")]
+    annotations: Vec<(ConstraintId, String)>,
 }
 
 //impl<'a, F: ScalarField> std::ops::Drop for Context<'a, F> {
@@ -231,6 +271,11 @@ impl<'a, F: ScalarField> std::fmt::Display for Context<'a, F> {
     }
 }
 
+/// Identifies a constraint by the advice position `(column index, row)` it was emitted at: the first row of an
+/// enabled gate, the destination cell of a copy constraint, or a looked up cell.
+#[cfg(feature = "display")]
+pub type ConstraintId = (usize, usize);
+
 // a single struct to package any configuration parameters we will need for constructing a new `Context`
 #[derive(Clone, Debug)]
 pub struct ContextParams {
@@ -257,7 +302,15 @@ impl<'a, F: ScalarField> Context<'a, F> {
             assigned_constants: FxHashMap::default(),
             zero_cell: None,
             cells_to_lookup: Vec::new(),
+            witness_gen: true,
+            strict: false,
+            num_lookups: 0,
+            #[cfg(feature = "display")]
+            lookup_budget: None,
+            range_checked: FxHashSet::default(),
+            public_cells: Vec::new(),
             current_phase: 0,
+            row_offset: 0,
             #[cfg(feature = "display")]
             op_count: FxHashMap::default(),
             #[cfg(feature = "display")]
@@ -266,6 +319,16 @@ impl<'a, F: ScalarField> Context<'a, F> {
             total_lookup_cells: [0; MAX_PHASE],
             #[cfg(feature = "display")]
             total_fixed: 0,
+            #[cfg(feature = "display")]
+            track_unconstrained: false,
+            #[cfg(feature = "display")]
+            advice_log: Vec::new(),
+            #[cfg(feature = "display")]
+            constrained_advice: FxHashSet::default(),
+            #[cfg(feature = "display")]
+            pending_annotation: None,
+            #[cfg(feature = "display")]
+            annotations: Vec::new(),
         }
     }
 
@@ -286,20 +349,235 @@ impl<'a, F: ScalarField> Context<'a, F> {
         self.region.next_phase();
         self.current_phase += 1;
         for advice_alloc in self.advice_alloc.iter_mut() {
-            *advice_alloc = (0, 0);
+            *advice_alloc = (0, self.row_offset);
         }
         assert!(self.current_phase < MAX_PHASE);
     }
 
+    /// Returns the phase advice is currently being assigned in: 0 initially, incremented by each `next_phase` call.
+    /// Phase-aware gadgets (e.g., ones using challenges, which are only available from phase 1) can branch on it.
     pub fn current_phase(&self) -> usize {
         self.current_phase
     }
 
+    /// Records `cell` as a public input. Cells are kept in the order they are marked.
+    pub fn mark_public(&mut self, cell: &AssignedValue<'a, F>) {
+        self.public_cells.push(cell.clone());
+    }
+
+    /// Returns all cells marked with `mark_public`, in order, for wiring into the instance column
+    pub fn public_cells(&self) -> &[AssignedValue<'a, F>] {
+        &self.public_cells
+    }
+
+    /// Sets whether this `Context` is generating witnesses. Set it to `false` during keygen, when all witnesses are unknown,
+    /// so that `when_known` skips expensive witness computations. Defaults to `true`.
+    pub fn set_witness_gen(&mut self, witness_gen: bool) {
+        self.witness_gen = witness_gen;
+    }
+
+    pub fn witness_gen(&self) -> bool {
+        self.witness_gen
+    }
+
+    /// Starts all advice, lookup advice, and fixed assignments of this `Context` at row `offset` of `region`, leaving
+    /// rows `0..offset` of its columns free for a hand-written circuit assigning in the same region. Must be called before
+    /// anything is assigned, and applies to every phase.
+    ///
+    /// Rows of the cells returned by `Context` stay relative to `region` like those of hand-written cells, so
+    /// `constrain_equal` works between the two directly, and cells in other regions can be copy constrained through
+    /// `AssignedValue::cell` once the layouter has placed both regions.
+    pub fn set_row_offset(&mut self, offset: usize) {
+        assert!(offset < self.max_rows, "row offset {offset} leaves no rows below max_rows");
+        assert!(
+            self.advice_alloc.iter().all(|alloc| *alloc == (0, self.row_offset))
+                && self.fixed_offset == self.row_offset
+                && self.fixed_col == 0,
+            "set_row_offset must be called before assigning any cells"
+        );
+        self.row_offset = offset;
+        self.fixed_offset = offset;
+        for advice_alloc in self.advice_alloc.iter_mut() {
+            *advice_alloc = (0, offset);
+        }
+    }
+
+    /// Returns the first row assigned by this `Context` in each of its columns, set by `set_row_offset`. Defaults to 0.
+    pub fn row_offset(&self) -> usize {
+        self.row_offset
+    }
+
+    /// Turns strict assignment on or off. When on, `assign_cell` panics if given a `Witness` or `WitnessFraction` whose
+    /// value is unknown, since during proving every witness must be known and an unknown one silently produces an invalid
+    /// proof. Strict mode must be off during keygen, when all witnesses are unknown. Defaults to off.
+    pub fn strict_mode(&mut self, on: bool) {
+        self.strict = on;
+    }
+
+    fn check_strict(&self, input: &QuantumCell<F>, row_offset: usize) {
+        if !self.strict {
+            return;
+        }
+        let known = match input {
+            QuantumCell::Witness(val) => value_to_option(val.as_ref()).is_some(),
+            QuantumCell::WitnessFraction(val) => value_to_option(val.as_ref()).is_some(),
+            _ => true,
+        };
+        assert!(known, "strict mode: unknown witness assigned at row {row_offset}");
+    }
+
+    /// Runs `f` and returns its output as a known `Value` if this `Context` is generating witnesses, and returns
+    /// `Value::unknown()` without running `f` otherwise (see `set_witness_gen`).
+    ///
+    /// `Value::map` is already lazy, so witnesses derived from an input `Value` are never computed when it is unknown.
+    /// This is for computations that do not start from a `Value`, e.g., ones reading host-side data captured by the circuit.
+    pub fn when_known<T>(&self, f: impl FnOnce() -> T) -> Value<T> {
+        if self.witness_gen {
+            Value::known(f())
+        } else {
+            Value::unknown()
+        }
+    }
+
+    /// Returns the number of cells looked up in this `Context` so far, across all phases
+    pub fn lookup_count(&self) -> usize {
+        self.num_lookups
+    }
+
+    /// Sets the maximum number of lookups allowed in this `Context`, to catch accidental lookup blowups during development.
+    ///
+    /// The budget is only enforced in debug builds: exceeding it panics in `count_lookup`.
+    #[cfg(feature = "display")]
+    pub fn set_lookup_budget(&mut self, max: usize) {
+        self.lookup_budget = Some(max);
+    }
+
+    /// Records that one more cell is looked up. Called by the range chip whenever it enables a lookup.
+    pub(crate) fn count_lookup(&mut self) {
+        self.num_lookups += 1;
+        #[cfg(feature = "display")]
+        if let Some(max) = self.lookup_budget {
+            debug_assert!(self.num_lookups <= max, "LOOKUP BUDGET OF {max} EXCEEDED");
+        }
+    }
+
+    /// Queues `cell` to be copied to the lookup enabled advice column in `copy_and_lookup_cells`, and counts the lookup.
+    pub fn push_lookup_cell(&mut self, cell: AssignedValue<'a, F>) {
+        self.count_lookup();
+        self.cells_to_lookup.push(cell);
+    }
+
+    /// Records that `a` is range checked to `range_bits` bits in this `Context`, and returns whether it was not already.
+    ///
+    /// Only the range chip may call this, right before emitting the range check: skipping a check based on the memo is only
+    /// sound if every entry corresponds to constraints that were actually emitted.
+    pub(crate) fn mark_range_checked(&mut self, a: &AssignedValue<F>, range_bits: usize) -> bool {
+        self.range_checked.insert((a.cell().column.index(), a.row(), range_bits))
+    }
+
+    /// Turns on or off recording the advice cells assigned and constrained through this `Context`, for
+    /// `unconstrained_cells`. Only cells assigned while it is on are tracked. Defaults to off.
+    #[cfg(feature = "display")]
+    pub fn track_unconstrained_cells(&mut self, on: bool) {
+        self.track_unconstrained = on;
+    }
+
+    /// Marks the advice cell `a` as constrained for the purposes of `unconstrained_cells`
+    #[cfg(feature = "display")]
+    pub fn mark_constrained(&mut self, a: &AssignedValue<F>) {
+        let key = (a.cell().column.index(), a.row());
+        self.attach_annotation(key);
+        if self.track_unconstrained {
+            self.constrained_advice.insert(key);
+        }
+    }
+
+    /// Marks rows `row..row + len` of `column` as constrained, e.g., the rows covered by an enabled gate selector
+    #[cfg(feature = "display")]
+    pub fn mark_rows_constrained(&mut self, column: Column<Advice>, row: usize, len: usize) {
+        let col = column.index();
+        self.attach_annotation((col, row));
+        if self.track_unconstrained {
+            self.constrained_advice.extend((row..row + len).map(|r| (col, r)));
+        }
+    }
+
+    /// Attaches `note` to the next constraint emitted through this `Context`, e.g., for exporting the constraints
+    /// to an external checker with [`Context::export_annotated`]. A later call before any constraint is emitted
+    /// replaces `note`.
+    ///
+    /// Constraints are seen in the order they are emitted, so the note of a gadget call attaches to its first
+    /// constraint, which is often the copy constraint of an `Existing` input rather than the gate itself.
+    #[cfg(feature = "display")]
+    pub fn annotate(&mut self, note: &str) {
+        self.pending_annotation = Some(note.to_string());
+    }
+
+    /// Returns the notes attached with [`Context::annotate`] in emission order, keyed by their constraint
+    #[cfg(feature = "display")]
+    pub fn export_annotated(&self) -> Vec<(ConstraintId, String)> {
+        self.annotations.clone()
+    }
+
+    #[cfg(feature = "display")]
+    fn attach_annotation(&mut self, id: ConstraintId) {
+        if let Some(note) = self.pending_annotation.take() {
+            self.annotations.push((id, note));
+        }
+    }
+
+    #[cfg(feature = "display")]
+    fn log_advice(&mut self, input: &QuantumCell<F>, column: Column<Advice>, row_offset: usize) {
+        let key = (column.index(), row_offset);
+        // `Existing` and `Constant` inputs are copy constrained
+        let copied = matches!(
+            input,
+            QuantumCell::Existing(_) | QuantumCell::ExistingOwned(_) | QuantumCell::Constant(_)
+        );
+        if copied {
+            self.attach_annotation(key);
+        }
+        if !self.track_unconstrained {
+            return;
+        }
+        self.advice_log.push(key);
+        match input {
+            QuantumCell::Existing(a) => self.mark_constrained(a),
+            QuantumCell::ExistingOwned(a) => self.mark_constrained(a),
+            _ => {}
+        }
+        if copied {
+            self.constrained_advice.insert(key);
+        }
+    }
+
+    /// Debugging aid: returns the indices, in assignment order, of the advice cells assigned through `assign_cell`
+    /// that were never seen in a gate, copy constraint, or lookup.
+    ///
+    /// This is a heuristic and has limitations:
+    /// * a cell counts as constrained as soon as any enabled gate covers its row, even if the gate does not
+    /// actually restrict its value;
+    /// * constraints added directly on `region` (instead of through `Context::constrain_equal` or the gate and range chips)
+    /// are not seen, so such cells are reported as unconstrained;
+    /// * the result is only meaningful once all gates and lookups of the circuit have been assigned;
+    /// * only cells assigned after `track_unconstrained_cells(true)` are tracked.
+    #[cfg(feature = "display")]
+    pub fn unconstrained_cells(&self) -> Vec<usize> {
+        self.advice_log
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| !self.constrained_advice.contains(key))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     #[cfg(feature = "display")]
     /// Returns (number of fixed columns used, total fixed cells used)
     pub fn fixed_stats(&self) -> (usize, usize) {
         // heuristic, fixed cells don't need to worry about blinding factors
-        ((self.total_fixed + self.max_rows - 1) / self.max_rows, self.total_fixed)
+        // rows `0..row_offset` are reserved in every column
+        let rows = self.max_rows - self.row_offset;
+        ((self.total_fixed + rows - 1) / rows, self.total_fixed)
     }
 
     #[cfg(feature = "halo2-axiom")]
@@ -367,6 +645,9 @@ impl<'a, F: ScalarField> Context<'a, F> {
         #[cfg(feature = "display")] context_id: usize,
         row_offset: usize,
     ) -> AssignedValue<'v, F> {
+        self.check_strict(&input, row_offset);
+        #[cfg(feature = "display")]
+        self.log_advice(&input, column, row_offset);
         match input {
             QuantumCell::Existing(acell) => {
                 AssignedValue {
@@ -433,6 +714,9 @@ impl<'a, F: ScalarField> Context<'a, F> {
         row_offset: usize,
         phase: u8,
     ) -> AssignedValue<'v, F> {
+        self.check_strict(&input, row_offset);
+        #[cfg(feature = "display")]
+        self.log_advice(&input, column, row_offset);
         match input {
             QuantumCell::Existing(acell) => {
                 AssignedValue {
@@ -510,6 +794,11 @@ impl<'a, F: ScalarField> Context<'a, F> {
 
     // convenience function to deal with rust warnings
     pub fn constrain_equal(&mut self, a: &AssignedValue<F>, b: &AssignedValue<F>) {
+        #[cfg(feature = "display")]
+        {
+            self.mark_constrained(a);
+            self.mark_constrained(b);
+        }
         #[cfg(feature = "halo2-axiom")]
         self.region.constrain_equal(a.cell(), b.cell());
         #[cfg(not(feature = "halo2-axiom"))]
@@ -523,7 +812,7 @@ impl<'a, F: ScalarField> Context<'a, F> {
         let total_cells = self.cells_to_lookup.len();
         let mut cells_to_lookup = self.cells_to_lookup.iter().peekable();
         for column in lookup_advice.into_iter() {
-            let mut offset = 0;
+            let mut offset = self.row_offset;
             while offset < self.max_rows && cells_to_lookup.peek().is_some() {
                 let acell = cells_to_lookup.next().unwrap();
                 acell.copy_advice(&mut self.region, column, offset);
@@ -545,14 +834,16 @@ impl<'a, F: ScalarField> Context<'a, F> {
     pub fn print_stats(&mut self, context_names: &[&str]) {
         let curr_phase = self.current_phase();
         self.advice_alloc_cache[curr_phase] = self.advice_alloc.clone();
+        // rows `0..row_offset` are reserved in every column and not counted as used
+        let rows = self.max_rows - self.row_offset;
         for phase in 0..=curr_phase {
             for (context_name, alloc) in
                 context_names.iter().zip(self.advice_alloc_cache[phase].iter())
             {
-                println!("Context \"{context_name}\" used {} advice columns and {} total advice cells in phase {phase}", alloc.0 + 1, alloc.0 * self.max_rows + alloc.1);
+                println!("Context \"{context_name}\" used {} advice columns and {} total advice cells in phase {phase}", alloc.0 + 1, alloc.0 * rows + alloc.1 - self.row_offset);
             }
             let num_lookup_advice_cells = self.total_lookup_cells[phase];
-            println!("Special lookup advice cells: optimal columns: {}, total {num_lookup_advice_cells} cells used in phase {phase}.",  (num_lookup_advice_cells + self.max_rows - 1)/self.max_rows);
+            println!("Special lookup advice cells: optimal columns: {}, total {num_lookup_advice_cells} cells used in phase {phase}.",  (num_lookup_advice_cells + rows - 1) / rows);
         }
         let (fixed_cols, total_fixed) = self.fixed_stats();
         println!("Fixed columns: {fixed_cols}, Total fixed cells: {total_fixed}");
@@ -1,12 +1,14 @@
 #[cfg(feature = "halo2-pse")]
 use crate::halo2_proofs::arithmetic::CurveAffine;
 use crate::halo2_proofs::{arithmetic::FieldExt, circuit::Value};
-use core::hash::Hash;
+use core::{cmp::Ordering, hash::Hash};
 use num_bigint::BigInt;
 use num_bigint::BigUint;
+use num_bigint::RandBigInt;
 use num_bigint::Sign;
+use num_integer::{ExtendedGcd, Integer};
 use num_traits::Signed;
-use num_traits::{One, Zero};
+use num_traits::{One, ToPrimitive, Zero};
 
 #[cfg(feature = "halo2-axiom")]
 pub trait BigPrimeField: ScalarField {
@@ -35,6 +37,11 @@ pub trait ScalarField: FieldExt + Hash {
     ///
     /// Undefined behavior if `bit_len > 64`
     fn to_u64_limbs(self, num_limbs: usize, bit_len: usize) -> Vec<u64>;
+
+    /// Returns the little endian u64 digits of the prime field element
+    ///
+    /// Only meaningful for fields of at most 256 bits
+    fn to_u64_array(&self) -> [u64; 4];
 }
 #[cfg(feature = "halo2-axiom")]
 impl<F> ScalarField for F
@@ -46,6 +53,11 @@ where
         let tmp: [u64; 4] = self.into();
         decompose_u64_digits_to_limbs(tmp, num_limbs, bit_len)
     }
+
+    #[inline(always)]
+    fn to_u64_array(&self) -> [u64; 4] {
+        (*self).into()
+    }
 }
 
 // Later: will need to separate PrimeField from ScalarField when Goldilocks is introduced
@@ -55,7 +67,25 @@ pub trait PrimeField = BigPrimeField;
 pub trait PrimeField = FieldExt<Repr = [u8; 32]>;
 
 #[cfg(feature = "halo2-pse")]
-pub trait ScalarField = FieldExt;
+pub trait ScalarField: FieldExt {
+    /// Returns the little endian u64 digits of the prime field element
+    ///
+    /// Only meaningful for fields of at most 256 bits: any bytes of `to_repr` past the first 32 are truncated
+    fn to_u64_array(&self) -> [u64; 4];
+}
+#[cfg(feature = "halo2-pse")]
+impl<F: FieldExt> ScalarField for F {
+    #[inline(always)]
+    fn to_u64_array(&self) -> [u64; 4] {
+        let mut digits = [0u64; 4];
+        for (digit, chunk) in digits.iter_mut().zip(self.to_repr().as_ref().chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *digit = u64::from_le_bytes(bytes);
+        }
+        digits
+    }
+}
 
 #[inline(always)]
 pub(crate) fn decompose_u64_digits_to_limbs(
@@ -99,6 +129,88 @@ pub fn bit_length(x: u64) -> usize {
     (u64::BITS - x.leading_zeros()) as usize
 }
 
+/// Returns the smallest power of two `>= n`, with `next_power_of_two(0) = 1`, as `2^log2_ceil(n)`.
+///
+/// Panics if `n > 2^63`.
+///
+/// ```
+/// use halo2_base::utils::next_power_of_two;
+///
+/// assert_eq!(next_power_of_two(5), 8);
+/// assert_eq!(next_power_of_two(8), 8);
+/// assert_eq!(next_power_of_two(0), 1);
+/// ```
+pub fn next_power_of_two(n: u64) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    let log_n = log2_ceil(n);
+    assert!(log_n < 64, "next_power_of_two overflows u64");
+    1 << log_n
+}
+
+/// Returns the number of bits of `e`, i.e., `e.bits()` as a `usize`; 0 for zero.
+///
+/// ```
+/// use halo2_base::utils::biguint_bits;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(biguint_bits(&BigUint::from(0u64)), 0);
+/// assert_eq!(biguint_bits(&(BigUint::from(1u64) << 100)), 101);
+/// ```
+pub fn biguint_bits(e: &BigUint) -> usize {
+    e.bits() as usize
+}
+
+/// Returns the number of bits of the magnitude of `e`.
+///
+/// ```
+/// use halo2_base::utils::bigint_bits;
+/// use num_bigint::BigInt;
+///
+/// assert_eq!(bigint_bits(&BigInt::from(-255)), 8);
+/// assert_eq!(bigint_bits(&BigInt::from(256)), 9);
+/// ```
+pub fn bigint_bits(e: &BigInt) -> usize {
+    biguint_bits(e.magnitude())
+}
+
+/// Returns the number of bits of the canonical representative of `fe` in `[0, p)`.
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::fe_bits;
+///
+/// assert_eq!(fe_bits(&Fr::from(5)), 3);
+/// assert_eq!(fe_bits(&-Fr::from(1)), 254);
+/// ```
+pub fn fe_bits<F: ScalarField>(fe: &F) -> usize {
+    biguint_bits(&fe_to_biguint(fe))
+}
+
+/// Returns the worst-case number of bits of a sum of `num_terms` products of an `a_limb_bits`-bit limb and a
+/// `b_limb_bits`-bit limb, i.e., of `num_terms * (2^a_limb_bits - 1) * (2^b_limb_bits - 1)`.
+///
+/// When multiplying limb vectors, each output position sums at most `num_limbs` such products, so the limb parameters
+/// are valid only if this is less than the number of bits of the native modulus, so no position wraps around.
+///
+/// ```
+/// use halo2_base::utils::limb_product_overflow_bits;
+///
+/// // 3 limbs of 88 bits: each output position fits in 178 < 254 bits of the BN254 scalar field
+/// assert_eq!(limb_product_overflow_bits(88, 88, 3), 178);
+/// assert_eq!(limb_product_overflow_bits(88, 88, 4), 178);
+/// assert_eq!(limb_product_overflow_bits(88, 88, 0), 0);
+/// ```
+pub fn limb_product_overflow_bits(
+    a_limb_bits: usize,
+    b_limb_bits: usize,
+    num_terms: usize,
+) -> usize {
+    let max_limb = |bits: usize| (BigUint::one() << bits) - 1u32;
+    biguint_bits(&(max_limb(a_limb_bits) * max_limb(b_limb_bits) * num_terms))
+}
+
 pub fn log2_ceil(x: u64) -> usize {
     (u64::BITS - x.leading_zeros() - (x & (x - 1) == 0) as u32) as usize
 }
@@ -152,10 +264,176 @@ pub fn bigint_to_fe<F: PrimeField>(e: &BigInt) -> F {
     }
 }
 
+/// Returns `e` as a field element together with its non-negative residue `e mod modulus` in `[0, modulus)`.
+///
+/// Unlike [`bigint_to_fe`], `|e|` may be larger than the modulus.
+pub fn bigint_to_fe_and_residue<F: PrimeField>(e: &BigInt) -> (F, BigUint) {
+    let residue = e.mod_floor(&BigInt::from(modulus::<F>())).to_biguint().unwrap();
+    (biguint_to_fe(&residue), residue)
+}
+
+/// Returns `num / den` in the field `F`, e.g., `1/3` as a gadget constant, or `None` if `den` is zero.
+///
+/// Negative `num` or `den` are mapped to their negations via [`bigint_to_fe`].
+pub fn fe_from_ratio<F: PrimeField>(num: i64, den: i64) -> Option<F> {
+    let num = bigint_to_fe::<F>(&BigInt::from(num));
+    let den_inv: Option<F> = bigint_to_fe::<F>(&BigInt::from(den)).invert().into();
+    den_inv.map(|den_inv| num * den_inv)
+}
+
+/// Converts a halo2 field element into the arkworks field `A` with the same modulus, e.g., BN254 `Fr` into `ark_bn254::Fr`.
+///
+/// The conversion goes through the canonical integer representative: `fe` is serialized as its canonical value in
+/// little-endian bytes (as `fe_to_biguint` does) and read with `A::from_le_bytes_mod_order`.
+/// If the moduli do not match the value is silently reduced modulo `A`'s modulus.
+#[cfg(feature = "ark")]
+pub fn fe_to_ark<F: PrimeField, A: ark_ff::PrimeField>(fe: &F) -> A {
+    A::from_le_bytes_mod_order(&fe_to_biguint(fe).to_bytes_le())
+}
+
+/// Converts an arkworks field element into the halo2 field `F` with the same modulus; the inverse of [`fe_to_ark`].
+///
+/// `a` is read as its canonical integer representative (`into_repr`, not the Montgomery form) in little-endian bytes.
+/// If the moduli do not match the value is reduced modulo `F`'s modulus.
+#[cfg(feature = "ark")]
+pub fn ark_to_fe<A: ark_ff::PrimeField, F: PrimeField>(a: &A) -> F {
+    use ark_ff::BigInteger;
+    let e = BigUint::from_bytes_le(&a.into_repr().to_bytes_le());
+    biguint_to_fe(&(e % modulus::<F>()))
+}
+
+/// Returns `i` as a field element, e.g., for absorbing small integer tags into a transcript
+pub fn index_to_fe<F: ScalarField>(i: u64) -> F {
+    F::from(i)
+}
+
+/// Returns each of `is` as a field element, as in [`index_to_fe`]
+pub fn indices_to_fe<F: ScalarField>(is: &[u64]) -> Vec<F> {
+    is.iter().map(|i| index_to_fe(*i)).collect()
+}
+
+/// Deterministically hashes `label` to a field element for domain separation:
+/// the SHA-256 digest of `label`, read as a big-endian integer and reduced modulo the field modulus.
+#[cfg(feature = "domain-tag")]
+pub fn domain_tag<F: PrimeField>(label: &str) -> F {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(label.as_bytes());
+    biguint_to_fe(&(BigUint::from_bytes_be(&digest) % modulus::<F>()))
+}
+
 pub fn fe_to_biguint<F: ff::PrimeField>(fe: &F) -> BigUint {
     BigUint::from_bytes_le(fe.to_repr().as_ref())
 }
 
+/// Parses a decimal string into a field element, reducing it modulo the field modulus.
+///
+/// Returns `None` if `s` is not a valid decimal number.
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::fe_from_dec_str;
+///
+/// assert_eq!(fe_from_dec_str::<Fr>("12345"), Some(Fr::from(12345)));
+/// assert_eq!(fe_from_dec_str::<Fr>("12a45"), None);
+/// ```
+pub fn fe_from_dec_str<F: PrimeField>(s: &str) -> Option<F> {
+    let e = BigUint::parse_bytes(s.as_bytes(), 10)?;
+    Some(biguint_to_fe(&(e % modulus::<F>())))
+}
+
+/// Same as [`fe_from_dec_str`] but returns `None` if the number is not less than the field modulus.
+pub fn fe_from_dec_str_canonical<F: PrimeField>(s: &str) -> Option<F> {
+    let e = BigUint::parse_bytes(s.as_bytes(), 10)?;
+    (e < modulus::<F>()).then(|| biguint_to_fe(&e))
+}
+
+/// Returns the 32-byte big-endian encoding of the canonical representative of `fe`, as used by Ethereum.
+///
+/// This is `to_repr` reversed for fields of at most 256 bits.
+pub fn fe_to_bytes_be<F: ScalarField>(fe: &F) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (chunk, digit) in bytes.chunks_mut(8).zip(fe.to_u64_array().iter().rev()) {
+        chunk.copy_from_slice(&digit.to_be_bytes());
+    }
+    bytes
+}
+
+/// Parses a 32-byte big-endian encoding into a field element; the inverse of [`fe_to_bytes_be`].
+///
+/// Returns `None` if the encoded integer is not less than the field modulus.
+pub fn fe_from_bytes_be<F: PrimeField>(bytes: &[u8; 32]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    for (r, b) in repr.as_mut().iter_mut().zip(bytes.iter().rev()) {
+        *r = *b;
+    }
+    F::from_repr(repr).into()
+}
+
+/// Packs the little-endian `bits` into a field element, or returns `None` if there are more than `F::NUM_BITS` bits
+/// or the packed value is not less than the modulus, instead of silently reducing it.
+pub fn try_bits_to_fe<F: PrimeField>(bits: &[bool]) -> Option<F> {
+    if bits.len() > F::NUM_BITS as usize {
+        return None;
+    }
+    let e = bits.iter().rev().fold(BigUint::zero(), |acc, bit| (acc << 1u32) + u32::from(*bit));
+    (e < modulus::<F>()).then(|| biguint_to_fe(&e))
+}
+
+/// Compares two field elements by their canonical representatives as unsigned integers in `[0, modulus)`
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::fe_cmp;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(fe_cmp(&Fr::from(3), &Fr::from(5)), Ordering::Less);
+/// assert_eq!(fe_cmp(&-Fr::from(1), &Fr::from(5)), Ordering::Greater);
+/// ```
+pub fn fe_cmp<F: PrimeField>(a: &F, b: &F) -> Ordering {
+    fe_to_biguint(a).cmp(&fe_to_biguint(b))
+}
+
+/// Returns a square root of `fe`, or `None` if `fe` is not a quadratic residue.
+///
+/// Of the two roots `r` and `-r`, returns the one whose canonical representative in `[0, p)` is smaller, so the
+/// result does not depend on the choice made by `ff::Field::sqrt`.
+pub fn fe_sqrt<F: PrimeField>(fe: &F) -> Option<F> {
+    let root = Option::<F>::from(fe.sqrt())?;
+    let neg = -root;
+    Some(if fe_cmp(&neg, &root) == Ordering::Less { neg } else { root })
+}
+
+/// Returns the Legendre symbol of `a`: 0 if `a` is zero, 1 if it is a nonzero square and -1 otherwise,
+/// computed as `a^((p - 1) / 2)`. Tells whether [`fe_sqrt`] will succeed, e.g., before decompressing a point.
+pub fn legendre_symbol<F: PrimeField>(a: &F) -> i8 {
+    let exp = (modulus::<F>() - 1u32) >> 1;
+    let symbol = a.pow_vartime(exp.to_u64_digits());
+    if symbol == F::zero() {
+        0
+    } else if symbol == F::one() {
+        1
+    } else {
+        debug_assert_eq!(symbol, -F::one());
+        -1
+    }
+}
+
+/// Samples a uniformly random field element less than `2^bits`, e.g., for fuzzing gadgets with bounded inputs
+///
+/// Any `rand::Rng` can be passed as `rng`. Panics if `bits > F::CAPACITY`.
+pub fn rand_fe_bounded<F: PrimeField>(rng: &mut impl RandBigInt, bits: usize) -> F {
+    assert!(bits <= F::CAPACITY as usize, "rand_fe_bounded: bits exceeds field capacity");
+    biguint_to_fe(&rng.gen_biguint(bits as u64))
+}
+
+/// Returns `n` uniformly random field elements from a `ChaCha20Rng` seeded with `seed`, so benchmarks and tests can
+/// use the same inputs across runs and machines.
+pub fn deterministic_fes<F: PrimeField>(seed: u64, n: usize) -> Vec<F> {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    (0..n).map(|_| F::random(&mut rng)).collect()
+}
+
 pub fn fe_to_bigint<F: PrimeField>(fe: &F) -> BigInt {
     // TODO: `F` should just have modulus as lazy_static or something
     let modulus = modulus::<F>();
@@ -167,6 +445,67 @@ pub fn fe_to_bigint<F: PrimeField>(fe: &F) -> BigInt {
     }
 }
 
+/// Returns the number of trailing zero bits in the canonical representation of `fe`,
+/// or `F::NUM_BITS` if `fe` is zero.
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::fe_trailing_zeros;
+///
+/// assert_eq!(fe_trailing_zeros(&Fr::from(8)), 3);
+/// ```
+pub fn fe_trailing_zeros<F: ScalarField>(fe: &F) -> u32 {
+    #[cfg(feature = "halo2-axiom")]
+    let trailing_zeros = fe
+        .to_u64_limbs(4, 64)
+        .into_iter()
+        .enumerate()
+        .find(|(_, digit)| *digit != 0)
+        .map(|(i, digit)| 64 * i as u32 + digit.trailing_zeros());
+    #[cfg(feature = "halo2-pse")]
+    let trailing_zeros = fe
+        .to_repr()
+        .as_ref()
+        .iter()
+        .enumerate()
+        .find(|(_, byte)| **byte != 0)
+        .map(|(i, byte)| 8 * i as u32 + byte.trailing_zeros());
+    trailing_zeros.unwrap_or(F::NUM_BITS)
+}
+
+/// Returns the number of set bits in the canonical representation of `fe`
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::fe_hamming_weight;
+///
+/// assert_eq!(fe_hamming_weight(&Fr::from(0b1011)), 3);
+/// ```
+pub fn fe_hamming_weight<F: ScalarField>(fe: &F) -> u32 {
+    #[cfg(feature = "halo2-axiom")]
+    {
+        fe.to_u64_limbs(4, 64).into_iter().map(|digit| digit.count_ones()).sum()
+    }
+    #[cfg(feature = "halo2-pse")]
+    {
+        fe.to_repr().as_ref().iter().map(|byte| byte.count_ones()).sum()
+    }
+}
+
+/// Returns whether `num_limbs` limbs of `bit_len` bits can hold every element of `F`,
+/// i.e., whether `num_limbs * bit_len >= F::NUM_BITS` so that decomposing into such limbs loses no bits.
+///
+/// ```
+/// use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+/// use halo2_base::utils::decomposition_is_lossless;
+///
+/// assert!(decomposition_is_lossless::<Fr>(4, 64));
+/// assert!(!decomposition_is_lossless::<Fr>(3, 64));
+/// ```
+pub fn decomposition_is_lossless<F: PrimeField>(num_limbs: usize, bit_len: usize) -> bool {
+    num_limbs * bit_len >= F::NUM_BITS as usize
+}
+
 pub fn decompose<F: PrimeField>(e: &F, number_of_limbs: usize, bit_len: usize) -> Vec<F> {
     if bit_len > 64 {
         decompose_biguint(&fe_to_biguint(e), number_of_limbs, bit_len)
@@ -222,6 +561,73 @@ pub fn decompose_biguint<F: PrimeField>(e: &BigUint, num_limbs: usize, bit_len:
         .collect()
 }
 
+/// Same as `decompose_biguint` but returns the limbs most significant first
+pub fn decompose_biguint_be<F: PrimeField>(
+    e: &BigUint,
+    num_limbs: usize,
+    bit_len: usize,
+) -> Vec<F> {
+    let mut limbs = decompose_biguint(e, num_limbs, bit_len);
+    limbs.reverse();
+    limbs
+}
+
+/// Decomposes `e` into little endian limbs where limb `i` has `bit_lens[i]` bits.
+///
+/// Like the other decompose functions, any bits of `e` beyond `sum(bit_lens)` are truncated.
+///
+/// Assumes each `bit_lens[i]` is less than the number of bits of the modulus of F
+pub fn decompose_biguint_mixed<F: PrimeField>(e: &BigUint, bit_lens: &[usize]) -> Vec<F> {
+    let mut e = e.clone();
+    bit_lens
+        .iter()
+        .map(|&bit_len| {
+            let limb = &e & ((BigUint::one() << bit_len) - 1u64);
+            e >>= bit_len;
+            biguint_to_fe(&limb)
+        })
+        .collect()
+}
+
+/// Returns exactly `n` little-endian u64 digits of `e`, zero-extending if `e` has fewer digits
+/// and dropping the most significant digits (i.e., reducing mod `2^(64 * n)`) if it has more.
+///
+/// ```
+/// use halo2_base::utils::biguint_to_u64_digits_padded;
+/// use num_bigint::BigUint;
+///
+/// let e = BigUint::from(1u64) << 64 | BigUint::from(5u64);
+/// assert_eq!(biguint_to_u64_digits_padded(&e, 3), vec![5, 1, 0]);
+/// assert_eq!(biguint_to_u64_digits_padded(&e, 1), vec![5]);
+/// ```
+pub fn biguint_to_u64_digits_padded(e: &BigUint, n: usize) -> Vec<u64> {
+    let mut digits = e.to_u64_digits();
+    digits.resize(n, 0);
+    digits
+}
+
+/// Returns `ceil(a / b)`, computed from the quotient and remainder of `a / b` instead of as `(a + b - 1) / b`.
+///
+/// Panics if `b` is zero.
+///
+/// ```
+/// use halo2_base::utils::div_ceil_biguint;
+/// use num_bigint::BigUint;
+///
+/// let b = BigUint::from(88u64);
+/// assert_eq!(div_ceil_biguint(&BigUint::from(256u64), &b), BigUint::from(3u64));
+/// assert_eq!(div_ceil_biguint(&BigUint::from(264u64), &b), BigUint::from(3u64));
+/// assert_eq!(div_ceil_biguint(&BigUint::from(0u64), &b), BigUint::from(0u64));
+/// ```
+pub fn div_ceil_biguint(a: &BigUint, b: &BigUint) -> BigUint {
+    let (q, r) = a.div_rem(b);
+    if r.is_zero() {
+        q
+    } else {
+        q + 1u32
+    }
+}
+
 pub fn decompose_bigint<F: PrimeField>(e: &BigInt, num_limbs: usize, bit_len: usize) -> Vec<F> {
     if e.is_negative() {
         decompose_biguint::<F>(e.magnitude(), num_limbs, bit_len).into_iter().map(|x| -x).collect()
@@ -230,6 +636,16 @@ pub fn decompose_bigint<F: PrimeField>(e: &BigInt, num_limbs: usize, bit_len: us
     }
 }
 
+/// Returns the CRT representation `(limbs, native)` of `e` used by the non-native field gadgets: the signed limbs
+/// of [`decompose_bigint`] and `e mod modulus::<F>()` as a native field element.
+///
+/// Assumes `|e| < 2^(num_limbs * limb_bits)`.
+pub fn to_crt<F: PrimeField>(e: &BigInt, num_limbs: usize, limb_bits: usize) -> (Vec<F>, F) {
+    let limbs = decompose_bigint(e, num_limbs, limb_bits);
+    let (native, _) = bigint_to_fe_and_residue(e);
+    (limbs, native)
+}
+
 pub fn decompose_bigint_option<F: PrimeField>(
     value: Value<&BigInt>,
     number_of_limbs: usize,
@@ -238,6 +654,34 @@ pub fn decompose_bigint_option<F: PrimeField>(
     value.map(|e| decompose_bigint(e, number_of_limbs, bit_len)).transpose_vec(number_of_limbs)
 }
 
+/// Inverts every element of `elems` in place using Montgomery's batch inversion trick,
+/// which costs a single field inversion and `3n` multiplications.
+///
+/// Zero elements are left unchanged.
+pub fn batch_invert<F: ScalarField>(elems: &mut [F]) {
+    // prods[i] = product of all nonzero elems[..i]
+    let mut acc = F::one();
+    let prods = elems
+        .iter()
+        .map(|e| {
+            let prod = acc;
+            if !e.is_zero_vartime() {
+                acc *= e;
+            }
+            prod
+        })
+        .collect::<Vec<_>>();
+    // acc is nonzero since it is a product of nonzero elements
+    let mut inv = acc.invert().unwrap();
+    for (e, prod) in elems.iter_mut().zip(prods.into_iter()).rev() {
+        if !e.is_zero_vartime() {
+            let e_inv = inv * prod;
+            inv *= *e;
+            *e = e_inv;
+        }
+    }
+}
+
 pub fn value_to_option<V>(value: Value<V>) -> Option<V> {
     let mut v = None;
     value.map(|val| {
@@ -246,15 +690,296 @@ pub fn value_to_option<V>(value: Value<V>) -> Option<V> {
     v
 }
 
+/// Wraps each element of `vs` in `Value::known`
+pub fn into_known_values<F: Clone>(vs: &[F]) -> Vec<Value<F>> {
+    vs.iter().map(|v| Value::known(v.clone())).collect()
+}
+
+/// Returns the values in `vs`, or `None` if any of them is unknown
+pub fn known_values_to_vec<F: Clone>(vs: &[Value<F>]) -> Option<Vec<F>> {
+    vs.iter().map(|v| value_to_option(v.as_ref()).cloned()).collect()
+}
+
+/// Formats `cell` for debug logs as its advice column index, row, and value in hex, or `?` if the value is unknown.
+///
+/// For example, a cell holding 42 in row 12 of the first advice column is formatted as
+/// ```text
+/// col=0 row=12 val=0x2a
+/// ```
+#[cfg(feature = "display")]
+pub fn assigned_to_string<F: ScalarField>(cell: &crate::AssignedValue<F>) -> String {
+    let val = value_to_hex_string(cell.value());
+    format!("col={} row={} val={val}", cell.cell().column.index(), cell.row())
+}
+
+// the value part of `assigned_to_string`
+#[cfg(feature = "display")]
+fn value_to_hex_string<F: ScalarField>(value: Value<&F>) -> String {
+    match value_to_option(value) {
+        Some(v) => format!("{:#x}", fe_to_biguint(v)),
+        None => "?".to_string(),
+    }
+}
+
+/// Returns the Montgomery constant `R = 2^(64 * num_limbs) mod p` where `p = modulus::<F>()`
+pub fn montgomery_r<F: PrimeField>(num_limbs: usize) -> BigUint {
+    (BigUint::one() << (64 * num_limbs)) % modulus::<F>()
+}
+
+/// Returns `R^2 mod p` for the Montgomery constant `R` of [`montgomery_r`]
+pub fn montgomery_r_squared<F: PrimeField>(num_limbs: usize) -> BigUint {
+    let r = montgomery_r::<F>(num_limbs);
+    (&r * &r) % modulus::<F>()
+}
+
+/// Returns whether `a == b`, comparing the canonical byte representations of all elements without early exit.
+///
+/// Threat model: use this when the elements are secret (e.g., comparing a recomputed MAC against a received one) and
+/// an attacker can measure the running time of the comparison. The running time depends only on the lengths of `a`
+/// and `b`, which are treated as public: slices of different lengths return `false` immediately. This only covers the
+/// comparison itself; computing `to_repr` and any surrounding host code are not audited to be constant time.
+#[cfg(feature = "ct")]
+pub fn ct_slice_eq<F: ScalarField>(a: &[F], b: &[F]) -> bool {
+    use subtle::{Choice, ConstantTimeEq};
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b)
+        .fold(Choice::from(1u8), |acc, (a, b)| {
+            acc & a.to_repr().as_ref().ct_eq(b.to_repr().as_ref())
+        })
+        .into()
+}
+
+/// Packs `bytes` into field elements, `bytes_per_fe` bytes at a time, each chunk read as a little-endian integer.
+/// The last chunk may be shorter.
+///
+/// `bytes_per_fe` must keep every chunk below the modulus so that packing is injective, e.g., at most 31 for BN254;
+/// this is enforced by requiring `8 * bytes_per_fe <= F::CAPACITY`.
+pub fn bytes_to_fes<F: PrimeField>(bytes: &[u8], bytes_per_fe: usize) -> Vec<F> {
+    assert!(bytes_per_fe > 0 && 8 * bytes_per_fe <= F::CAPACITY as usize);
+    bytes.chunks(bytes_per_fe).map(|chunk| biguint_to_fe(&BigUint::from_bytes_le(chunk))).collect()
+}
+
+/// Inverse of [`bytes_to_fes`]: unpacks `bytes_per_fe` little-endian bytes from each element and returns the first
+/// `num_bytes` of them, since the length of the original last chunk is not recorded in the field elements.
+pub fn fes_to_bytes<F: PrimeField>(fes: &[F], bytes_per_fe: usize, num_bytes: usize) -> Vec<u8> {
+    assert!(num_bytes <= fes.len() * bytes_per_fe);
+    let mut bytes = Vec::with_capacity(fes.len() * bytes_per_fe);
+    for fe in fes {
+        let mut chunk = fe_to_biguint(fe).to_bytes_le();
+        assert!(chunk.len() <= bytes_per_fe, "field element does not fit in {bytes_per_fe} bytes");
+        chunk.resize(bytes_per_fe, 0);
+        bytes.extend(chunk);
+    }
+    bytes.truncate(num_bytes);
+    bytes
+}
+
+/// Returns the Merkle root obtained by hashing `leaf` up along `path`, as a host-side reference for Merkle gadgets.
+///
+/// `path` lists `(sibling, is_right)` from the leaf level up, where `is_right` is whether the current node is the right
+/// child, so each level computes `hasher(sibling, node)` if `is_right` and `hasher(node, sibling)` otherwise.
+pub fn merkle_root<F: PrimeField>(leaf: F, path: &[(F, bool)], hasher: impl Fn(F, F) -> F) -> F {
+    path.iter().fold(
+        leaf,
+        |node, &(sibling, is_right)| {
+            if is_right {
+                hasher(sibling, node)
+            } else {
+                hasher(node, sibling)
+            }
+        },
+    )
+}
+
+/// Returns the Morton code of the low `bits` bits of `a` and `b`: bit `i` of `a` goes to bit `2i` and bit `i` of `b`
+/// to bit `2i + 1`. Panics if `bits > 64`.
+pub fn interleave_bits(a: u64, b: u64, bits: usize) -> u128 {
+    assert!(bits <= 64);
+    (0..bits).fold(0u128, |z, i| {
+        z | (((a >> i) & 1) as u128) << (2 * i) | (((b >> i) & 1) as u128) << (2 * i + 1)
+    })
+}
+
+/// Inverse of [`interleave_bits`]: returns `(a, b)` from the even and odd bits of the low `2 * bits` bits of `z`.
+/// Panics if `bits > 64`.
+pub fn deinterleave_bits(z: u128, bits: usize) -> (u64, u64) {
+    assert!(bits <= 64);
+    (0..bits).fold((0u64, 0u64), |(a, b), i| {
+        (a | (((z >> (2 * i)) & 1) as u64) << i, b | (((z >> (2 * i + 1)) & 1) as u64) << i)
+    })
+}
+
+/// Returns the 2-adicity `S` of `F`, i.e., the largest `S` such that `2^S` divides `p - 1`
+pub fn two_adicity<F: PrimeField>() -> u32 {
+    F::S
+}
+
+/// Returns the fixed primitive `2^S`-th root of unity of `F`, where `S = two_adicity::<F>()`
+pub fn root_of_unity<F: PrimeField>() -> F {
+    F::root_of_unity()
+}
+
+/// Returns a primitive `2^log_n`-th root of unity, obtained by squaring `root_of_unity::<F>()` `S - log_n` times
+///
+/// Panics if `log_n > two_adicity::<F>()`.
+pub fn root_of_unity_pow<F: PrimeField>(log_n: u32) -> F {
+    assert!(log_n <= F::S, "no 2^{log_n}-th root of unity in a field of 2-adicity {}", F::S);
+    (log_n..F::S).fold(F::root_of_unity(), |omega, _| omega.square())
+}
+
+/// Returns `Z_H(x) = x^n - 1`, the vanishing polynomial of the multiplicative subgroup `H` of order `n`, at `x`
+pub fn vanishing_eval<F: ff::Field>(x: F, n: u64) -> F {
+    x.pow_vartime([n]) - F::one()
+}
+
+/// Returns `L_i(x)` for the `i`-th Lagrange basis polynomial of the domain `H = {omega^j : 0 <= j < n}`, where `omega`
+/// is a primitive `n`-th root of unity (e.g., from `root_of_unity_pow`), i.e., `L_i(omega^j) = 1` if `i = j` and 0 otherwise.
+///
+/// Uses `L_i(x) = omega^i * (x^n - 1) / (n * (x - omega^i))`, and returns 1 at `x = omega^i`.
+pub fn lagrange_basis_eval<F: PrimeField>(x: F, i: u64, n: u64, omega: F) -> F {
+    let omega_i = omega.pow_vartime([i]);
+    if x == omega_i {
+        return F::one();
+    }
+    omega_i * vanishing_eval(x, n) * (F::from(n) * (x - omega_i)).invert().unwrap()
+}
+
+/// Returns the evaluations `p(omega^i)` for `i = 0..n` of the polynomial `p` with coefficients `coeffs`, using an
+/// iterative radix-2 Cooley-Tukey transform. Host-side reference for validating in-circuit NTTs.
+///
+/// `n = coeffs.len()` must be a power of two and `omega` a primitive `n`-th root of unity, e.g., `root_of_unity_pow`.
+pub fn ntt<F: ScalarField>(coeffs: &[F], omega: F) -> Vec<F> {
+    let n = coeffs.len();
+    assert!(n.is_power_of_two(), "ntt size {n} is not a power of two");
+    let mut a = coeffs.to_vec();
+    if n > 1 {
+        let shift = usize::BITS - n.trailing_zeros();
+        for i in 0..n {
+            let j = i.reverse_bits() >> shift;
+            if i < j {
+                a.swap(i, j);
+            }
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let omega_len = omega.pow_vartime([(n / len) as u64]);
+        for chunk in a.chunks_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            let mut w = F::one();
+            for (u, v) in lo.iter_mut().zip(hi.iter_mut()) {
+                let t = *v * w;
+                *v = *u - t;
+                *u += t;
+                w *= omega_len;
+            }
+        }
+        len *= 2;
+    }
+    a
+}
+
+/// Returns the coefficients of the polynomial with evaluations `evals` on the powers of `omega`; the inverse of [`ntt`].
+pub fn intt<F: ScalarField>(evals: &[F], omega: F) -> Vec<F> {
+    let n_inv = F::from(evals.len() as u64).invert().unwrap();
+    let mut coeffs = ntt(evals, omega.invert().unwrap());
+    for c in coeffs.iter_mut() {
+        *c *= n_inv;
+    }
+    coeffs
+}
+
 /// Compute the represented value by a vector of values and a bit length.
 ///
 /// This function is used to compute the value of an integer
 /// passing as input its limb values and the bit length used.
 /// Returns the sum of all limbs scaled by 2^(bit_len * i)
+///
+/// Limbs are not checked to be less than `2^bit_len`, so an oversized limb silently
+/// produces a value outside the expected range. Use [`checked_compose`] to detect this.
 pub fn compose(input: Vec<BigUint>, bit_len: usize) -> BigUint {
     input.iter().rev().fold(BigUint::zero(), |acc, val| (acc << bit_len) + val)
 }
 
+/// Same as [`compose`] but returns `None` if any limb is `>= 2^bit_len`.
+pub fn checked_compose(input: &[BigUint], bit_len: usize) -> Option<BigUint> {
+    if input.iter().any(|limb| limb.bits() > bit_len as u64) {
+        return None;
+    }
+    Some(compose(input.to_vec(), bit_len))
+}
+
+/// Propagates carries through `limbs` so that every limb is `< 2^bit_len`, appending new high limbs as needed.
+///
+/// The represented value `compose(limbs, bit_len)` is unchanged. This is the host-side counterpart of carrying
+/// the limbs of a non-native sum back to `bit_len` bits.
+pub fn normalize_limbs(limbs: &mut Vec<BigUint>, bit_len: usize) {
+    assert!(bit_len > 0);
+    let mut carry = BigUint::zero();
+    for limb in limbs.iter_mut() {
+        let sum = &*limb + &carry;
+        carry = &sum >> bit_len;
+        *limb = sum - (&carry << bit_len);
+    }
+    while !carry.is_zero() {
+        let high = &carry >> bit_len;
+        limbs.push(&carry - (&high << bit_len));
+        carry = high;
+    }
+}
+
+/// Returns `(g, x, y)` such that `g = gcd(a, b)` and `a * x + b * y = g`
+///
+/// If `g = 1` then `x mod b` is the inverse of `a` modulo `b`.
+pub fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    let ExtendedGcd { gcd, x, y, .. } = a.extended_gcd(b);
+    (gcd, x, y)
+}
+
+/// Returns `a^{-1} mod modulus` in `[0, modulus)` using [`extended_gcd`], or `None` if `gcd(a, modulus) != 1`.
+///
+/// `modulus` is arbitrary, e.g., a non-native field modulus. Panics if `modulus` is zero.
+pub fn mod_inverse(a: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    assert!(!modulus.is_zero());
+    let m = BigInt::from(modulus.clone());
+    let (g, x, _) = extended_gcd(&BigInt::from(a.clone()), &m);
+    // `mod_floor` also reduces an inverse of 0 modulo 1 to 0
+    g.is_one().then(|| x.mod_floor(&m).to_biguint().unwrap())
+}
+
+/// Returns the residues `e mod m_i` for each modulus `m_i` in `moduli`.
+///
+/// This is the residue number system (RNS) representation of `e`; it is only
+/// injective for `e < prod_i m_i` when the moduli are pairwise coprime.
+pub fn rns_decompose(e: &BigUint, moduli: &[u64]) -> Vec<u64> {
+    moduli.iter().map(|&m| (e % m).to_u64().unwrap()).collect()
+}
+
+/// Reconstructs the unique `e < prod_i m_i` with `e mod m_i = residues[i]` using Garner's algorithm.
+///
+/// Assumes `moduli` are pairwise coprime and `residues[i] < moduli[i]`.
+pub fn rns_compose(residues: &[u64], moduli: &[u64]) -> BigUint {
+    assert_eq!(residues.len(), moduli.len());
+    // mixed radix digits `v_i` such that `e = v_0 + v_1 * m_0 + v_2 * m_0 * m_1 + ...`
+    let mut digits: Vec<u64> = Vec::with_capacity(moduli.len());
+    for (&r, &m) in residues.iter().zip(moduli.iter()) {
+        let m = m as u128;
+        // compute `v_0 + v_1 * m_0 + ... mod m` and `m_0 * ... * m_{i-1} mod m`
+        let (acc, prod) =
+            digits.iter().zip(moduli.iter()).fold((0u128, 1u128), |(acc, prod), (&v, &m_j)| {
+                ((acc + v as u128 * prod) % m, prod * (m_j as u128 % m) % m)
+            });
+        let inv = (prod as i128).extended_gcd(&(m as i128)).x.mod_floor(&(m as i128)) as u128;
+        let diff = (r as u128 % m + m - acc) % m;
+        digits.push((diff * inv % m) as u64);
+    }
+    digits.iter().zip(moduli.iter()).rev().fold(BigUint::zero(), |acc, (&v, &m)| acc * m + v)
+}
+
 #[cfg(test)]
 #[test]
 fn test_signed_roundtrip() {
@@ -262,6 +987,526 @@ fn test_signed_roundtrip() {
     assert_eq!(fe_to_bigint(&bigint_to_fe::<Fr>(&-BigInt::one())), -BigInt::one());
 }
 
+#[cfg(test)]
+#[test]
+fn test_extended_gcd() {
+    let a = BigInt::from(240);
+    let b = BigInt::from(46);
+    let (g, x, y) = extended_gcd(&a, &b);
+    assert_eq!(g, BigInt::from(2));
+    assert_eq!(&a * &x + &b * &y, g);
+
+    // modular inverse of 17 mod the bn254 scalar field modulus
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    let p = BigInt::from(modulus::<Fr>());
+    let a = BigInt::from(17);
+    let (g, x, _) = extended_gcd(&a, &p);
+    assert!(g.is_one());
+    let inv = x.mod_floor(&p);
+    assert!((&a * &inv).mod_floor(&p).is_one());
+    assert_eq!(bigint_to_fe::<Fr>(&inv), Fr::from(17).invert().unwrap());
+}
+
+#[cfg(test)]
+#[test]
+fn test_rns_roundtrip() {
+    let moduli = [1_000_003u64, 998_244_353, 1_000_000_007];
+    let e = BigUint::from(123_456_789_012_345_678_901_234u128);
+    let residues = rns_decompose(&e, &moduli);
+    assert_eq!(rns_compose(&residues, &moduli), e);
+}
+
+#[cfg(test)]
+#[test]
+fn test_checked_compose() {
+    let limbs = [3u64, 1, 2].map(BigUint::from);
+    assert_eq!(checked_compose(&limbs, 2), Some(compose(limbs.to_vec(), 2)));
+    let limbs = [3u64, 4, 2].map(BigUint::from);
+    assert_eq!(checked_compose(&limbs, 2), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_batch_invert() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    let elems = [3u64, 0, 7, 1, 12345].map(Fr::from);
+    let mut inverted = elems;
+    batch_invert(&mut inverted);
+    for (e, inv) in elems.iter().zip(inverted.iter()) {
+        assert_eq!(*inv, e.invert().unwrap_or(Fr::zero()));
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_decompose_biguint_mixed() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let e: u128 = 0x1234_5678_9abc_def0_0fed_cba9_8765_4321;
+    let limbs = decompose_biguint_mixed::<Fr>(&BigUint::from(e), &[40, 40, 48]);
+    let expected = [e & ((1 << 40) - 1), (e >> 40) & ((1 << 40) - 1), e >> 80];
+    assert_eq!(limbs, expected.map(Fr::from_u128));
+}
+
+#[cfg(test)]
+#[test]
+fn test_decompose_biguint_be() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let e = BigUint::from(0x1234_5678_9abc_def0_0fed_cba9_8765_4321u128) << 100;
+    let mut limbs = decompose_biguint::<Fr>(&e, 3, 88);
+    limbs.reverse();
+    assert_eq!(decompose_biguint_be::<Fr>(&e, 3, 88), limbs);
+}
+
+#[cfg(test)]
+#[test]
+fn test_to_u64_array() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let x = (7u128 << 64) + 3;
+    assert_eq!(Fr::from_u128(x).to_u64_array(), [3, 7, 0, 0]);
+    let mut digits = fe_to_biguint(&-Fr::one()).to_u64_digits();
+    digits.resize(4, 0);
+    assert_eq!((-Fr::one()).to_u64_array().to_vec(), digits);
+}
+
+#[cfg(test)]
+#[test]
+fn test_fe_from_dec_str() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let modulus = modulus::<Fr>();
+    assert_eq!(fe_from_dec_str::<Fr>(&(&modulus + 5u64).to_string()), Some(Fr::from(5)));
+    assert_eq!(fe_from_dec_str_canonical::<Fr>(&(&modulus + 5u64).to_string()), None);
+    assert_eq!(fe_from_dec_str_canonical::<Fr>("5"), Some(Fr::from(5)));
+    assert_eq!(fe_from_dec_str::<Fr>("-5"), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_known_values() {
+    let vs = [1u64, 2, 3];
+    let values = into_known_values(&vs);
+    assert_eq!(known_values_to_vec(&values), Some(vs.to_vec()));
+    let mut values = values;
+    values[1] = Value::unknown();
+    assert_eq!(known_values_to_vec(&values), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_rand_fe_bounded() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for bits in [0, 1, 8, 64, 200, 253] {
+        for _ in 0..10 {
+            let x = rand_fe_bounded::<Fr>(&mut rng, bits);
+            assert!(fe_to_biguint(&x).bits() <= bits as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_bigint_to_fe_and_residue() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let modulus = modulus::<Fr>();
+    for e in [0i64, 1, -1, 12345, -12345] {
+        let e = BigInt::from(e);
+        let (fe, residue) = bigint_to_fe_and_residue::<Fr>(&e);
+        assert_eq!(fe, bigint_to_fe::<Fr>(&e));
+        assert!(residue < modulus);
+        assert_eq!(
+            BigInt::from(residue.clone()) - &e,
+            BigInt::from(modulus.clone()) * (e < BigInt::zero()) as u8
+        );
+    }
+    let (fe, residue) = bigint_to_fe_and_residue::<Fr>(&BigInt::from(-1));
+    assert_eq!(fe, -Fr::one());
+    assert_eq!(residue, &modulus - 1u64);
+    // inputs larger than the modulus are reduced
+    let (fe, residue) = bigint_to_fe_and_residue::<Fr>(&-BigInt::from(&modulus * 2u64 + 3u64));
+    assert_eq!(fe, -Fr::from(3));
+    assert_eq!(residue, &modulus - 3u64);
+}
+
+#[cfg(test)]
+#[test]
+fn test_montgomery_r() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let hex = |s: &str| BigUint::parse_bytes(s.as_bytes(), 16).unwrap();
+    assert_eq!(
+        montgomery_r::<Fr>(4),
+        hex("e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb")
+    );
+    assert_eq!(
+        montgomery_r_squared::<Fr>(4),
+        hex("216d0b17f4e44a58c49833d53bb808553fe3ab1e35c59e31bb8e645ae216da7")
+    );
+    assert_eq!(montgomery_r::<Fr>(0), BigUint::one());
+}
+
+#[cfg(test)]
+#[test]
+fn test_root_of_unity_pow() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    assert_eq!(two_adicity::<Fr>(), 28);
+    for log_n in [0, 1, 5, 28] {
+        let omega = root_of_unity_pow::<Fr>(log_n);
+        let pow = (0..log_n).fold(omega, |x, _| x.square());
+        assert_eq!(pow, Fr::one());
+        if log_n > 0 {
+            // primitive: omega^(2^(log_n - 1)) = -1
+            let half = (1..log_n).fold(omega, |x, _| x.square());
+            assert_eq!(half, -Fr::one());
+        }
+    }
+    assert_eq!(root_of_unity_pow::<Fr>(28), root_of_unity::<Fr>());
+}
+
+#[cfg(test)]
+#[test]
+fn test_bytes_to_fes_roundtrip() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let bytes = (0..100u8).map(|i| i.wrapping_mul(37).wrapping_add(200)).collect::<Vec<_>>();
+    for bytes_per_fe in [1, 7, 31] {
+        let fes = bytes_to_fes::<Fr>(&bytes, bytes_per_fe);
+        assert_eq!(fes.len(), (bytes.len() + bytes_per_fe - 1) / bytes_per_fe);
+        assert_eq!(fes_to_bytes(&fes, bytes_per_fe, bytes.len()), bytes);
+    }
+    assert_eq!(bytes_to_fes::<Fr>(&[1, 2], 31), vec![Fr::from(0x0201u64)]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_merkle_root() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    // a non-commutative toy hash so that the left/right order matters
+    let hasher = |l: Fr, r: Fr| l * Fr::from(3u64) + r;
+    let leaves = [1u64, 2, 3, 4].map(Fr::from);
+    let (n01, n23) = (hasher(leaves[0], leaves[1]), hasher(leaves[2], leaves[3]));
+    let root = hasher(n01, n23);
+    assert_eq!(merkle_root(leaves[0], &[(leaves[1], false), (n23, false)], hasher), root);
+    assert_eq!(merkle_root(leaves[1], &[(leaves[0], true), (n23, false)], hasher), root);
+    assert_eq!(merkle_root(leaves[2], &[(leaves[3], false), (n01, true)], hasher), root);
+    assert_eq!(merkle_root(leaves[3], &[(leaves[2], true), (n01, true)], hasher), root);
+    assert_ne!(merkle_root(leaves[3], &[(leaves[2], false), (n01, true)], hasher), root);
+    assert_eq!(merkle_root(leaves[0], &[], hasher), leaves[0]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_normalize_limbs() {
+    let bit_len = 8;
+    // the limbwise sum of two normalized 3-limb numbers, plus an oversized limb needing two new high limbs
+    let cases =
+        [vec![255u64 + 255, 255 + 1, 128 + 200], vec![1 << 20, 0, 3], vec![], vec![255, 255, 255]];
+    for case in cases {
+        let mut limbs = case.iter().map(|x| BigUint::from(*x)).collect::<Vec<_>>();
+        let value = compose(limbs.clone(), bit_len);
+        normalize_limbs(&mut limbs, bit_len);
+        assert!(limbs.iter().all(|limb| limb.bits() <= bit_len as u64));
+        assert_eq!(compose(limbs.clone(), bit_len), value);
+        assert_eq!(checked_compose(&limbs, bit_len), Some(value));
+    }
+    let mut limbs = vec![BigUint::from(1u64 << 17)];
+    normalize_limbs(&mut limbs, bit_len);
+    assert_eq!(limbs, [0u64, 0, 2].map(BigUint::from));
+}
+
+#[cfg(test)]
+#[test]
+fn test_deterministic_fes() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let fes = deterministic_fes::<Fr>(42, 10);
+    assert_eq!(fes.len(), 10);
+    assert_eq!(fes, deterministic_fes::<Fr>(42, 10));
+    // a shorter stream is a prefix of a longer one from the same seed
+    assert_eq!(deterministic_fes::<Fr>(42, 3), fes[..3]);
+    assert_ne!(deterministic_fes::<Fr>(43, 10), fes);
+}
+
+#[cfg(test)]
+#[test]
+fn test_interleave_bits() {
+    assert_eq!(interleave_bits(0b11, 0b00, 2), 0b0101);
+    assert_eq!(interleave_bits(0b00, 0b11, 2), 0b1010);
+    assert_eq!(interleave_bits(0b101, 0b110, 3), 0b111_001);
+    // bits above `bits` are ignored
+    assert_eq!(interleave_bits(0xff, 0, 4), 0b0101_0101);
+    for (a, b) in
+        [(0u64, 0u64), (1, 2), (0xdead_beef, 0x1234_5678), (u64::MAX, 0), (u64::MAX, u64::MAX)]
+    {
+        assert_eq!(deinterleave_bits(interleave_bits(a, b, 64), 64), (a, b));
+    }
+    assert_eq!(deinterleave_bits(interleave_bits(0xabc, 0x123, 12), 12), (0xabc, 0x123));
+}
+
+#[cfg(test)]
+#[test]
+fn test_fe_sqrt() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    assert_eq!(fe_sqrt(&Fr::zero()), Some(Fr::zero()));
+    for x in 1..20u64 {
+        let x = Fr::from(x);
+        assert_eq!(fe_sqrt(&x.square()), Some(x));
+        assert_eq!(fe_sqrt(&(-x).square()), Some(x));
+        // 7 generates the multiplicative group of the BN254 scalar field, so it is a non-residue
+        assert_eq!(fe_sqrt(&(Fr::from(7u64) * x.square())), None);
+    }
+    let root = fe_sqrt(&-Fr::one()).unwrap();
+    assert_eq!(root.square(), -Fr::one());
+    assert_eq!(fe_cmp(&root, &-root), Ordering::Less);
+}
+
+#[cfg(test)]
+#[test]
+fn test_mod_inverse() {
+    let p = BigUint::from(101u64);
+    assert_eq!(mod_inverse(&BigUint::from(3u64), &p), Some(BigUint::from(34u64)));
+    // inputs larger than the modulus are reduced
+    assert_eq!(mod_inverse(&BigUint::from(104u64), &p), Some(BigUint::from(34u64)));
+    assert_eq!(mod_inverse(&BigUint::from(0u64), &p), None);
+    assert_eq!(mod_inverse(&BigUint::from(6u64), &BigUint::from(9u64)), None);
+    // secp256k1 base field modulus
+    let q = BigUint::parse_bytes(
+        b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        16,
+    )
+    .unwrap();
+    let a = BigUint::from(0xdead_beefu64) << 100;
+    let inv = mod_inverse(&a, &q).unwrap();
+    assert!(inv < q);
+    assert_eq!((a * inv) % &q, BigUint::one());
+}
+
+#[cfg(test)]
+#[test]
+fn test_lagrange_basis_eval() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    let n = 4u64;
+    let omega = root_of_unity_pow::<Fr>(2);
+    let domain = (0..n).map(|j| omega.pow_vartime([j])).collect::<Vec<_>>();
+    for (j, x) in domain.iter().enumerate() {
+        assert_eq!(vanishing_eval(*x, n), Fr::zero());
+        for i in 0..n {
+            let expected = if i == j as u64 { Fr::one() } else { Fr::zero() };
+            assert_eq!(lagrange_basis_eval(*x, i, n, omega), expected);
+        }
+    }
+    assert_eq!(vanishing_eval(Fr::from(2u64), n), Fr::from(15u64));
+
+    // interpolating p(X) = 3 + 2X + X^3 from its values on the domain recovers it off the domain
+    let p = |x: Fr| Fr::from(3u64) + Fr::from(2u64) * x + x.pow_vartime([3]);
+    let x = Fr::from(12345u64);
+    let interpolated = (0..n)
+        .map(|i| p(domain[i as usize]) * lagrange_basis_eval(x, i, n, omega))
+        .fold(Fr::zero(), |acc, term| acc + term);
+    assert_eq!(interpolated, p(x));
+}
+
+#[cfg(test)]
+#[test]
+fn test_ntt() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    for log_n in 0..6 {
+        let n = 1usize << log_n;
+        let omega = root_of_unity_pow::<Fr>(log_n);
+        let coeffs = deterministic_fes::<Fr>(log_n as u64, n);
+        let evals = ntt(&coeffs, omega);
+        for (i, eval) in evals.iter().enumerate() {
+            let x = omega.pow_vartime([i as u64]);
+            let expected = coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c);
+            assert_eq!(*eval, expected);
+        }
+        assert_eq!(intt(&evals, omega), coeffs);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_legendre_symbol() {
+    use crate::halo2_proofs::halo2curves::bn256::Fq;
+    assert_eq!(legendre_symbol(&Fq::zero()), 0);
+    assert_eq!(legendre_symbol(&Fq::one()), 1);
+    assert_eq!(legendre_symbol(&Fq::from(4)), 1);
+    // p = 3 mod 4 for the BN254 base field, so -1 is a non-residue
+    assert_eq!(legendre_symbol(&-Fq::one()), -1);
+    assert_eq!(legendre_symbol(&-Fq::from(4)), -1);
+    // 3 is a non-residue since p = 1 mod 3 and p = 3 mod 4
+    assert_eq!(legendre_symbol(&Fq::from(3)), -1);
+    for a in deterministic_fes::<Fq>(0, 16) {
+        assert_eq!(legendre_symbol(&a) == 1, fe_sqrt(&a).is_some());
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_try_bits_to_fe() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    use ff::PrimeField;
+    let to_bits = |e: &BigUint, len: usize| (0..len as u64).map(|i| e.bit(i)).collect::<Vec<_>>();
+    let num_bits = Fr::NUM_BITS as usize;
+    let p = modulus::<Fr>();
+
+    assert_eq!(try_bits_to_fe::<Fr>(&[]), Some(Fr::zero()));
+    assert_eq!(try_bits_to_fe::<Fr>(&[true, false, true, true]), Some(Fr::from(13)));
+    assert_eq!(try_bits_to_fe::<Fr>(&to_bits(&(&p - 1u32), num_bits)), Some(-Fr::one()));
+    // exactly the modulus, and above it
+    assert_eq!(try_bits_to_fe::<Fr>(&to_bits(&p, num_bits)), None);
+    assert_eq!(try_bits_to_fe::<Fr>(&[true; 254]), None);
+    // too many bits, even if the extra bits are zero
+    assert_eq!(try_bits_to_fe::<Fr>(&vec![false; num_bits + 1]), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_to_crt() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let (num_limbs, limb_bits) = (3, 88);
+    let p = BigInt::from(modulus::<Fr>());
+    for e in [
+        BigInt::from(0u64),
+        BigInt::from(-12345i64),
+        (BigInt::one() << 260) - 7u64,
+        -(BigInt::one() << 255) + 3u64,
+    ] {
+        let (limbs, native) = to_crt::<Fr>(&e, num_limbs, limb_bits);
+        assert_eq!(limbs.len(), num_limbs);
+        let composed = limbs
+            .iter()
+            .enumerate()
+            .fold(BigInt::zero(), |acc, (i, limb)| acc + (fe_to_bigint(limb) << (i * limb_bits)));
+        assert_eq!(composed, e);
+        assert_eq!(fe_to_biguint(&native), composed.mod_floor(&p).to_biguint().unwrap());
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_fe_from_ratio() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    let third = fe_from_ratio::<Fr>(1, 3).unwrap();
+    assert_eq!(third * Fr::from(3), Fr::one());
+    assert_eq!(fe_from_ratio::<Fr>(-2, 3).unwrap(), -third - third);
+    assert_eq!(fe_from_ratio::<Fr>(2, -3).unwrap(), -third - third);
+    assert_eq!(fe_from_ratio::<Fr>(-6, -3).unwrap(), Fr::from(2));
+    assert_eq!(fe_from_ratio::<Fr>(0, 7).unwrap(), Fr::zero());
+    assert_eq!(fe_from_ratio::<Fr>(1, 0), None);
+}
+
+#[cfg(test)]
+#[test]
+fn test_fe_bytes_be() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    use ff::PrimeField;
+    for fe in [Fr::zero(), Fr::one(), -Fr::one(), Fr::from(0x0102030405060708u64)]
+        .into_iter()
+        .chain(deterministic_fes::<Fr>(0, 8))
+    {
+        let bytes = fe_to_bytes_be(&fe);
+        let mut repr = fe.to_repr();
+        repr.as_mut().reverse();
+        assert_eq!(bytes.as_slice(), repr.as_ref());
+        assert_eq!(fe_from_bytes_be::<Fr>(&bytes), Some(fe));
+    }
+    assert_eq!(fe_to_bytes_be(&Fr::one())[31], 1);
+
+    // the modulus itself and anything above it are not canonical
+    let mut p_bytes = [0u8; 32];
+    p_bytes.copy_from_slice(&modulus::<Fr>().to_bytes_be());
+    assert_eq!(fe_from_bytes_be::<Fr>(&p_bytes), None);
+    assert_eq!(fe_from_bytes_be::<Fr>(&[0xff; 32]), None);
+}
+
+#[cfg(all(test, feature = "display"))]
+#[test]
+fn test_value_to_hex_string() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    assert_eq!(value_to_hex_string(Value::known(&Fr::from(42u64))), "0x2a");
+    assert_eq!(value_to_hex_string(Value::known(&Fr::zero())), "0x0");
+    assert_eq!(value_to_hex_string::<Fr>(Value::unknown()), "?");
+}
+
+#[cfg(all(test, feature = "test-utils"))]
+#[test]
+fn test_assert_valid_decomposition() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let e = BigUint::from(0x1234_5678u64);
+    test_utils::assert_valid_decomposition(&e, &decompose_biguint::<Fr>(&e, 3, 12), 12);
+    let oversized = std::panic::catch_unwind(|| {
+        test_utils::assert_valid_decomposition(&e, &[Fr::from(0x5678u64), Fr::from(0x1234u64)], 12)
+    });
+    assert!(oversized.is_err());
+    let wrong = std::panic::catch_unwind(|| {
+        test_utils::assert_valid_decomposition(&e, &decompose_biguint::<Fr>(&e, 2, 12), 12)
+    });
+    assert!(wrong.is_err());
+}
+
+#[cfg(all(test, feature = "ct"))]
+#[test]
+fn test_ct_slice_eq() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    let a = [1u64, 2, 3].map(Fr::from);
+    assert!(ct_slice_eq(&a, &a));
+    assert!(ct_slice_eq::<Fr>(&[], &[]));
+    assert!(!ct_slice_eq(&a, &[1u64, 2, 4].map(Fr::from)));
+    assert!(!ct_slice_eq(&a, &a[..2]));
+}
+
+#[cfg(all(test, feature = "ark"))]
+#[test]
+fn test_ark_roundtrip() {
+    use crate::halo2_proofs::{arithmetic::Field, halo2curves::bn256::Fr};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for fe in [Fr::zero(), Fr::one(), -Fr::one(), Fr::random(&mut rng), Fr::random(&mut rng)] {
+        let a: ark_bn254::Fr = fe_to_ark(&fe);
+        assert_eq!(ark_to_fe::<_, Fr>(&a), fe);
+    }
+    assert_eq!(fe_to_ark::<Fr, ark_bn254::Fr>(&Fr::from(12345)), ark_bn254::Fr::from(12345u64));
+    assert_eq!(fe_to_ark::<Fr, ark_bn254::Fr>(&-Fr::one()), -ark_bn254::Fr::from(1u64));
+}
+
+#[cfg(all(test, feature = "domain-tag"))]
+#[test]
+fn test_domain_tag() {
+    use crate::halo2_proofs::halo2curves::bn256::Fr;
+    assert_eq!(
+        domain_tag::<Fr>("halo2-base"),
+        fe_from_dec_str::<Fr>(
+            "18326179704099313141284930258749179187188934757027978867897215014009103087681"
+        )
+        .unwrap()
+    );
+    assert_ne!(domain_tag::<Fr>("halo2-base"), domain_tag::<Fr>("halo2-ecc"));
+    assert_eq!(indices_to_fe::<Fr>(&[0, 7]), vec![Fr::zero(), index_to_fe(7)]);
+}
+
+#[cfg(all(test, feature = "point-compression"))]
+#[test]
+fn test_point_compression_roundtrip() {
+    use crate::halo2_proofs::halo2curves::bn256::{G1Affine, G1};
+    use compression::{point_from_compressed_bytes, point_to_compressed_bytes};
+    use group::{prime::PrimeCurveAffine, Curve, Group};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..20 {
+        let p = G1::random(&mut rng).to_affine();
+        let bytes = point_to_compressed_bytes(&p);
+        assert_eq!(bytes.len(), 33);
+        assert_eq!(point_from_compressed_bytes::<G1Affine>(&bytes), Some(p));
+    }
+    let identity = G1Affine::identity();
+    let bytes = point_to_compressed_bytes(&identity);
+    assert_eq!(bytes, vec![0u8; 33]);
+    assert_eq!(point_from_compressed_bytes::<G1Affine>(&bytes), Some(identity));
+    // unknown flag
+    let mut bytes = point_to_compressed_bytes(&G1Affine::generator());
+    bytes[0] = 0x04;
+    assert_eq!(point_from_compressed_bytes::<G1Affine>(&bytes), None);
+}
+
 #[cfg(feature = "halo2-axiom")]
 pub use halo2_proofs_axiom::halo2curves::CurveAffineExt;
 
@@ -276,11 +1521,82 @@ pub trait CurveAffineExt: CurveAffine {
 #[cfg(feature = "halo2-pse")]
 impl<C: CurveAffine> CurveAffineExt for C {}
 
+/// Compressed encoding of affine curve points.
+///
+/// A point `p` is encoded as `flag || x`, where `x` is `C::Base::to_repr()` of the x-coordinate
+/// (in the field's own byte order, little-endian for the curves in `halo2curves`) and `flag` is a single byte:
+/// * `0x00` for the identity, in which case `x` is all zeros;
+/// * `0x02 | parity` otherwise, where `parity` is `1` if the y-coordinate is odd.
+///
+/// Decoding recovers `y` from the curve equation `y^2 = x^3 + a x + b`.
+#[cfg(feature = "point-compression")]
+pub mod compression {
+    use super::CurveAffineExt;
+    use crate::halo2_proofs::arithmetic::CurveAffine;
+    use ff::PrimeField;
+
+    pub fn point_to_compressed_bytes<C: CurveAffineExt>(p: &C) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(1 + <C::Base as PrimeField>::Repr::default().as_ref().len());
+        if bool::from(p.is_identity()) {
+            bytes.push(0x00);
+            bytes.extend_from_slice(<C::Base as PrimeField>::Repr::default().as_ref());
+        } else {
+            let (x, y) = p.into_coordinates();
+            bytes.push(0x02 | u8::from(bool::from(y.is_odd())));
+            bytes.extend_from_slice(x.to_repr().as_ref());
+        }
+        bytes
+    }
+
+    /// Inverse of [`point_to_compressed_bytes`]. Returns `None` if `bytes` is not a valid encoding of a point on the curve.
+    pub fn point_from_compressed_bytes<C: CurveAffineExt>(bytes: &[u8]) -> Option<C> {
+        let (flag, x_bytes) = bytes.split_first()?;
+        let mut repr = <C::Base as PrimeField>::Repr::default();
+        if x_bytes.len() != repr.as_ref().len() {
+            return None;
+        }
+        repr.as_mut().copy_from_slice(x_bytes);
+        match flag {
+            0x00 => x_bytes.iter().all(|b| *b == 0).then(C::identity),
+            0x02 | 0x03 => {
+                let x = Option::<C::Base>::from(C::Base::from_repr(repr))?;
+                let y2 = x.square() * x + C::a() * x + C::b();
+                let y = Option::<C::Base>::from(y2.sqrt())?;
+                let y = if bool::from(y.is_odd()) == (*flag == 0x03) { y } else { -y };
+                Option::from(C::from_xy(x, y))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Assertions shared by gadget tests, including those of downstream crates
+#[cfg(feature = "test-utils")]
+pub mod test_utils {
+    use super::{compose, fe_to_biguint, PrimeField};
+    use num_bigint::BigUint;
+
+    /// Panics with a descriptive message unless every limb is `< 2^bit_len` and the little-endian limbs compose to `e`
+    pub fn assert_valid_decomposition<F: PrimeField>(e: &BigUint, limbs: &[F], bit_len: usize) {
+        let limbs = limbs.iter().map(fe_to_biguint).collect::<Vec<_>>();
+        for (i, limb) in limbs.iter().enumerate() {
+            assert!(
+                limb.bits() <= bit_len as u64,
+                "limb {i} = {limb:#x} does not fit in {bit_len} bits"
+            );
+        }
+        let composed = compose(limbs, bit_len);
+        assert_eq!(&composed, e, "limbs compose to {composed:#x} instead of {e:#x}");
+    }
+}
+
 pub mod fs {
     use std::{
         env::var,
         fs::{self, File},
         io::{BufReader, BufWriter},
+        path::PathBuf,
     };
 
     use crate::halo2_proofs::{
@@ -292,34 +1608,83 @@ pub mod fs {
     };
     use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
 
+    /// Errors from reading or writing params files
+    #[derive(Debug)]
+    pub enum FsError {
+        Io(std::io::Error),
+    }
+
+    impl From<std::io::Error> for FsError {
+        fn from(err: std::io::Error) -> Self {
+            Self::Io(err)
+        }
+    }
+
+    impl std::fmt::Display for FsError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Io(err) => write!(f, "params io error: {err}"),
+            }
+        }
+    }
+
+    impl std::error::Error for FsError {}
+
+    fn params_dir() -> PathBuf {
+        PathBuf::from(var("PARAMS_DIR").unwrap_or_else(|_| "./params".to_string()))
+    }
+
+    /// Returns the path of the `k` params file read and written by the functions in this module,
+    /// i.e., `$PARAMS_DIR/kzg_bn254_{k}.srs` with `PARAMS_DIR` defaulting to `./params`.
+    pub fn srs_path(k: u32) -> PathBuf {
+        params_dir().join(format!("kzg_bn254_{k}.srs"))
+    }
+
     pub fn read_params(k: u32) -> ParamsKZG<Bn256> {
-        let dir = var("PARAMS_DIR").unwrap_or_else(|_| "./params".to_string());
         ParamsKZG::<Bn256>::read(&mut BufReader::new(
-            File::open(format!("{dir}/kzg_bn254_{k}.srs").as_str())
-                .expect("Params file does not exist"),
+            File::open(srs_path(k)).expect("Params file does not exist"),
         ))
         .unwrap()
     }
 
+    /// Same as `read_params` but memory-maps the params file and deserializes from the mapped bytes,
+    /// which avoids copying the whole file into a buffer when it is already in the OS page cache.
+    ///
+    /// Falls back to buffered reading if the file cannot be memory-mapped.
+    #[cfg(feature = "memmap")]
+    pub fn mmap_params(k: u32) -> Result<ParamsKZG<Bn256>, FsError> {
+        let file = File::open(srs_path(k))?;
+        // Safety: the params file is not expected to be modified while it is mapped
+        match unsafe { memmap2::Mmap::map(&file) } {
+            Ok(mmap) => Ok(ParamsKZG::<Bn256>::read(&mut &mmap[..])?),
+            Err(_) => Ok(ParamsKZG::<Bn256>::read(&mut BufReader::new(file))?),
+        }
+    }
+
     pub fn read_or_create_srs<'a, C: CurveAffine, P: ParamsProver<'a, C>>(
         k: u32,
         setup: impl Fn(u32) -> P,
     ) -> P {
-        let dir = var("PARAMS_DIR").unwrap_or_else(|_| "./params".to_string());
-        let path = format!("{dir}/kzg_bn254_{k}.srs");
-        match File::open(path.as_str()) {
+        let path = srs_path(k);
+        match File::open(&path) {
             Ok(f) => {
                 #[cfg(feature = "display")]
-                println!("read params from {path}");
+                println!("read params from {}", path.display());
                 let mut reader = BufReader::new(f);
                 P::read(&mut reader).unwrap()
             }
             Err(_) => {
                 #[cfg(feature = "display")]
                 println!("creating params for {k}");
-                fs::create_dir_all(dir).unwrap();
+                fs::create_dir_all(params_dir()).unwrap();
                 let params = setup(k);
-                params.write(&mut BufWriter::new(File::create(path).unwrap())).unwrap();
+                // write to a temporary file in the same directory and rename it into place,
+                // so a crash mid-write never leaves a truncated params file at `path`
+                let tmp_path = format!("{}.tmp.{}", path.display(), std::process::id());
+                let mut writer = BufWriter::new(File::create(&tmp_path).unwrap());
+                params.write(&mut writer).unwrap();
+                writer.into_inner().unwrap().sync_all().unwrap();
+                fs::rename(tmp_path, path).unwrap();
                 params
             }
         }